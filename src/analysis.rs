@@ -0,0 +1,954 @@
+use crate::stats::*;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+// Define a struct for the calculation settings
+pub struct Settings {
+    pub paired: bool,
+    pub two_tailed: bool,
+    pub confidence: f64,
+    pub iterations: i32,
+    pub zthresh: f64,
+    pub adjust: Adjust,
+    pub missing: Missing,
+    // Fixed seed for the resampling, None draws a new one, results hold the seed used
+    pub seed: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+// Define how rows with a missing value are handled in paired data
+pub enum Missing {
+    // Leave the whole row out of every statistic
+    Listwise,
+    // Leave the row out only where the missing value is needed
+    Pairwise,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+// Define a struct for the missing values found in the input
+pub struct Missingresults {
+    pub counts: Vec<usize>,
+    pub dropped: usize,
+}
+
+#[derive(Clone, Debug)]
+// Define a struct for one column of input data, NaN marks a missing value so paired rows stay aligned
+pub struct Sample {
+    pub name: String,
+    pub values: Vec<f64>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+// Define a struct for our dmeans and dsds
+pub struct Sdmeanresults {
+    pub amu: f64,
+    pub aml: f64,
+    pub amm: f64,
+    pub asu: f64,
+    pub asl: f64,
+    pub asm: f64,
+    pub bmu: f64,
+    pub bml: f64,
+    pub bmm: f64,
+    pub bsu: f64,
+    pub bsl: f64,
+    pub bsm: f64,
+    pub dmu: f64,
+    pub dml: f64,
+    pub dmm: f64,
+    pub dsu: f64,
+    pub dsl: f64,
+    pub dsm: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+// Define a struct for the descriptive stats of a single sample
+pub struct Descriptive {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub variance: f64,
+    pub median: f64,
+    pub mode: Option<f64>,
+    pub se: f64,
+    pub skewness: f64,
+    pub kurtosis: f64,
+    pub zcount: Zscoreresults,
+    pub unique_values: Vec<f64>,
+    pub unique_counts: Vec<i32>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+// Define a struct for paired correlation results
+pub struct Correlation {
+    pub spearman: f64,
+    pub p: f64,
+    pub adj_p: f64,
+    pub r2: f64,
+    pub pearson: Statresults,
+    pub kendall: Statresults,
+    pub slope: Statresults,
+    pub intercept: Statresults,
+}
+
+#[derive(Clone, Debug, Serialize)]
+// Define a struct for Bland-Altman agreement of paired data, differences are B - A
+pub struct Blandaltman {
+    pub means: Vec<f64>,
+    pub diffs: Vec<f64>,
+    pub bias: Statresults,
+    pub lower: Statresults,
+    pub upper: Statresults,
+    pub slope: Statresults,
+    pub intercept: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+// Define a struct for a two sample comparison
+pub struct Comparison {
+    pub settings: Settings,
+    pub a: Descriptive,
+    pub b: Descriptive,
+    pub sdmean: Sdmeanresults,
+    pub mean_p: f64,
+    pub adj_mean_p: f64,
+    pub sd_p: f64,
+    pub adj_sd_p: f64,
+    pub cohen_d: f64,
+    pub f: f64,
+    pub f_p: f64,
+    pub adj_f_p: f64,
+    pub correlation: Option<Correlation>,
+    pub blandaltman: Option<Blandaltman>,
+    #[serde(skip)]
+    pub boot_diff: Vec<f64>,
+    #[serde(skip)]
+    pub samples: Vec<Sample>,
+    pub missing: Missingresults,
+    pub tests: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+// Define a struct for the bootstrap ANOVA omnibus test
+pub struct Anovaresults {
+    pub f: f64,
+    pub df1: usize,
+    pub df2: usize,
+    pub p: f64,
+    pub boot_p: f64,
+    pub adj_boot_p: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+// Define a struct for the rank omnibus test, Kruskal-Wallis or Friedman for paired data
+pub struct Kruskalresults {
+    pub h: f64,
+    pub df: usize,
+    pub p: f64,
+    pub adj_p: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+// Define a struct for one pairwise comparison, the difference is group j - group i
+pub struct Pairresults {
+    pub i: usize,
+    pub j: usize,
+    pub diff: CIresults,
+    pub p: f64,
+    pub adj_p: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+// Define a struct for a k sample comparison
+pub struct Multisample {
+    pub settings: Settings,
+    pub names: Vec<String>,
+    pub groups: Vec<Descriptive>,
+    pub cis: Vec<CIresults>,
+    pub anova: Anovaresults,
+    pub kruskal: Kruskalresults,
+    pub pairs: Vec<Pairresults>,
+    #[serde(skip)]
+    pub samples: Vec<Sample>,
+    pub missing: Missingresults,
+    pub tests: usize,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            paired: false,
+            two_tailed: true,
+            confidence: 95.0,
+            iterations: 10000,
+            zthresh: 3.0,
+            adjust: Adjust::None,
+            missing: Missing::Listwise,
+            seed: None,
+        }
+    }
+}
+
+// Sessions save the seed as a TOML integer, which is signed, so seeds stay below 2^63
+pub const MAX_SEED: u64 = i64::MAX as u64;
+
+// Read a typed seed, refusing one too large to save
+pub fn parse_seed(text: &str) -> Result<u64, String> {
+    text.trim()
+        .parse::<u64>()
+        .ok()
+        .filter(|s| *s <= MAX_SEED)
+        .ok_or("Seed Error, use a whole number below 2^63".to_string())
+}
+
+impl Settings {
+    // Check the settings, returning the alert text for the first bad value
+    pub fn check(&self) -> Result<(), String> {
+        if !(1000..=9999000).contains(&self.iterations) {
+            return Err("Iteration Count Error".to_string());
+        }
+        // Both ends would index past the bootstrap distribution
+        if !(self.confidence > 0.0 && self.confidence < 100.0) {
+            return Err("Confidence Level Error".to_string());
+        }
+        if self.seed.is_some_and(|s| s > MAX_SEED) {
+            return Err("Seed Error, use a whole number below 2^63".to_string());
+        }
+        Ok(())
+    }
+
+    // Seed the resampling and keep the seed used so the run can be repeated
+    fn seeded(&self) -> (Settings, StdRng) {
+        let seed = draw_seed(self.seed);
+        (
+            Settings {
+                seed: Some(seed),
+                ..self.clone()
+            },
+            StdRng::seed_from_u64(seed),
+        )
+    }
+
+    // Significance level for the omnibus tests, which have no tails
+    pub fn alpha(&self) -> f64 {
+        (100.0 - self.confidence) / 100.0
+    }
+
+    // Convert to percentage, if it is a one tailed operation divide the confidence level in half
+    pub fn clevel(&self) -> f64 {
+        let mut clevel: f64 = (100.0 - self.confidence) / 100.0;
+
+        if !self.two_tailed {
+            clevel /= 2.0;
+        }
+        clevel
+    }
+}
+
+impl Missing {
+    // Name the handling for display
+    pub fn name(&self) -> &'static str {
+        match self {
+            Missing::Listwise => "Listwise",
+            Missing::Pairwise => "Pairwise",
+        }
+    }
+
+    // Find a handling by its name, ignoring case
+    pub fn from_name(name: &str) -> Result<Missing, String> {
+        match name.trim().to_lowercase().as_str() {
+            "listwise" => Ok(Missing::Listwise),
+            "pairwise" => Ok(Missing::Pairwise),
+            m => Err(format!("Unknown missing value handling {}", m)),
+        }
+    }
+}
+
+// Name a data column by its position, A through Z then AA, AB and so on
+pub fn group_name(i: usize) -> String {
+    if i < 26 {
+        ((b'A' + i as u8) as char).to_string()
+    } else {
+        format!("{}{}", group_name(i / 26 - 1), group_name(i % 26))
+    }
+}
+
+// Compare two samples
+pub fn compare(a: &Sample, b: &Sample, s: &Settings) -> Result<Comparison, String> {
+    s.check()?;
+    let (s, mut rng) = s.seeded();
+    let s = &s;
+
+    // For paired data make sure both columns have the same number of rows
+    if s.paired && a.values.len() != b.values.len() {
+        return Err("Data Fields Must Have Same Count for Paired Data".to_string());
+    }
+
+    // Complete pairs feed the paired statistics, pairwise handling keeps every value for the rest
+    let (pa, pb) = if s.paired {
+        complete_pair(&a.values, &b.values)
+    } else {
+        (present(&a.values), present(&b.values))
+    };
+    let (a_v, b_v) = if s.paired && s.missing == Missing::Pairwise {
+        (&present(&a.values), &present(&b.values))
+    } else {
+        (&pa, &pb)
+    };
+
+    if a_v.is_empty() || b_v.is_empty() {
+        return Err("Each Data Field Needs At Least One Value".to_string());
+    }
+    if s.paired && pa.is_empty() {
+        return Err("No Complete Pairs in Paired Data".to_string());
+    }
+
+    let missing = Missingresults {
+        counts: vec![missing_count(&a.values), missing_count(&b.values)],
+        dropped: if s.paired {
+            a.values.len() - pa.len()
+        } else {
+            0
+        },
+    };
+    let clevel = s.clevel();
+
+    // Check for paired or unpaired data
+    let (sdmeanresults, boot_diff) = if s.paired {
+        paired_data(a_v, b_v, &pa, &pb, s.iterations, clevel, &mut rng)
+    } else {
+        unpaired_data(a_v, b_v, s.iterations, clevel, &mut rng)
+    };
+
+    let mean_d = sdmeanresults.dmm;
+    let sd_a = sdmeanresults.asm;
+    let sd_b = sdmeanresults.bsm;
+    let sd_d = sdmeanresults.dsm;
+
+    let sd_pooled = ((sd_a * sd_a + sd_b * sd_b) / 2.0).sqrt();
+
+    let mut f: f64 = 1.0;
+    let mut f_a: usize = a_v.len();
+    let mut f_b: usize = b_v.len();
+
+    if sd_a > sd_b {
+        f = (sd_a * sd_a) / (sd_b * sd_b);
+        f_a = a_v.len();
+        f_b = b_v.len();
+    }
+    if sd_a < sd_b {
+        f = (sd_b * sd_b) / (sd_a * sd_a);
+        f_a = b_v.len();
+        f_b = a_v.len();
+    }
+
+    let f_p = p_from_f(f, f_a - 1, f_b - 1);
+
+    // Check for paired correlation data
+    let correlation = if s.paired && pa.len() > 1 {
+        let (a_v, b_v) = (&pa, &pb);
+        let r = r_value(rankify(a_v), rankify(b_v));
+        let dof = a_v.len() as f64 - 2.0;
+        let tr = r / ((1.0 - r * r) / dof).sqrt();
+
+        let pr = p_from_t(tr, dof);
+
+        // Bootstrap the linear measures of B on A together
+        let mut boot = ci_pairs(
+            a_v,
+            b_v,
+            s.iterations,
+            clevel,
+            &[pearson, kendall, ols_slope, ols_intercept],
+            &mut rng,
+        )
+        .into_iter();
+
+        let pearson = boot.next().unwrap();
+
+        // Kendall's Tau-b keeps the bootstrap CI but takes its own p value
+        let mut kendall = boot.next().unwrap();
+        kendall.p = p_from_kendall(a_v, b_v);
+        kendall.adj_p = kendall.p;
+
+        Some(Correlation {
+            spearman: r,
+            p: pr,
+            adj_p: pr,
+            r2: r2_value(a_v, b_v),
+            pearson,
+            kendall,
+            slope: boot.next().unwrap(),
+            intercept: boot.next().unwrap(),
+        })
+    } else {
+        None
+    };
+
+    // Check for paired agreement data
+    let blandaltman = if s.paired && pa.len() > 1 {
+        Some(bland_altman(&pa, &pb, s.iterations, clevel, &mut rng))
+    } else {
+        None
+    };
+
+    let mean_p = p_from_ci(sdmeanresults.dml, sdmeanresults.dmu, mean_d, 1.0 - clevel);
+    let sd_p = p_from_ci(sdmeanresults.dsl, sdmeanresults.dsu, sd_d, 1.0 - clevel);
+
+    let mut c = Comparison {
+        settings: s.clone(),
+        a: describe(a_v, sdmeanresults.amm, sdmeanresults.asm, s.zthresh),
+        b: describe(b_v, sdmeanresults.bmm, sdmeanresults.bsm, s.zthresh),
+        mean_p,
+        adj_mean_p: mean_p,
+        sd_p,
+        adj_sd_p: sd_p,
+        cohen_d: mean_d / sd_pooled,
+        f: 1.0 / f,
+        f_p: f_p * 2.0,
+        adj_f_p: f_p * 2.0,
+        sdmean: sdmeanresults,
+        correlation,
+        blandaltman,
+        boot_diff: thin(&boot_diff, 10000),
+        samples: vec![
+            Sample {
+                name: a.name.clone(),
+                values: a_v.clone(),
+            },
+            Sample {
+                name: b.name.clone(),
+                values: b_v.clone(),
+            },
+        ],
+        missing,
+        tests: 0,
+    };
+
+    c.adjust();
+
+    Ok(c)
+}
+
+// Compare three or more samples
+pub fn compare_many(samples: &[Sample], s: &Settings) -> Result<Multisample, String> {
+    s.check()?;
+    let (s, mut rng) = s.seeded();
+    let s = &s;
+
+    if s.paired
+        && samples
+            .iter()
+            .any(|g| g.values.len() != samples[0].values.len())
+    {
+        return Err("Data Fields Must Have Same Count for Paired Data".to_string());
+    }
+
+    // Rows with any missing value, listwise handling leaves them out everywhere
+    let incomplete: Vec<bool> = (0..samples[0].values.len())
+        .map(|r| s.paired && samples.iter().any(|g| g.values[r].is_nan()))
+        .collect();
+    let values: Vec<Vec<f64>> = samples
+        .iter()
+        .map(|g| {
+            if s.paired && s.missing == Missing::Listwise {
+                g.values
+                    .iter()
+                    .zip(&incomplete)
+                    .filter(|(_, i)| !**i)
+                    .map(|(v, _)| *v)
+                    .collect()
+            } else {
+                present(&g.values)
+            }
+        })
+        .collect();
+
+    if values.iter().any(|v| v.len() < 2) {
+        return Err("Each Data Field Needs At Least Two Values".to_string());
+    }
+
+    let missing = Missingresults {
+        counts: samples.iter().map(|g| missing_count(&g.values)).collect(),
+        dropped: incomplete.iter().filter(|i| **i).count(),
+    };
+    let clevel = s.clevel();
+    let cis: Vec<CIresults> = values
+        .iter()
+        .map(|v| ci(v, s.iterations, clevel, &mut rng))
+        .collect();
+    let groups: Vec<Descriptive> = values
+        .iter()
+        .zip(cis.iter())
+        .map(|(v, c)| describe(v, c.mm, c.sm, s.zthresh))
+        .collect();
+
+    // Omnibus tests, paired data gets the repeated measures versions over the complete rows
+    let k = values.len();
+    let (f, df2, boot_p, h) = if s.paired {
+        let rows: Vec<Vec<f64>> = samples
+            .iter()
+            .map(|g| {
+                g.values
+                    .iter()
+                    .zip(&incomplete)
+                    .filter(|(_, i)| !**i)
+                    .map(|(v, _)| *v)
+                    .collect()
+            })
+            .collect();
+        let n = rows[0].len();
+        if n < 2 {
+            return Err("Paired Data Needs At Least Two Complete Rows".to_string());
+        }
+        (
+            rm_anova_f(&rows),
+            (k - 1) * (n - 1),
+            bootstrap_rm_anova(&rows, s.iterations, &mut rng),
+            friedman_q(&rows),
+        )
+    } else {
+        let n: usize = values.iter().map(|v| v.len()).sum();
+        (
+            anova_f(&values),
+            n - k,
+            bootstrap_anova(&values, s.iterations, &mut rng),
+            kruskal_h(&values),
+        )
+    };
+
+    let anova = Anovaresults {
+        f,
+        df1: k - 1,
+        df2,
+        p: p_from_f(f, k - 1, df2),
+        boot_p,
+        adj_boot_p: boot_p,
+    };
+
+    let kp = p_from_chi2(h, (k - 1) as f64);
+    let kruskal = Kruskalresults {
+        h,
+        df: k - 1,
+        p: kp,
+        adj_p: kp,
+    };
+
+    // Pairwise comparisons of every group against every later group
+    let mut pairs: Vec<Pairresults> = Vec::new();
+
+    for i in 0..k {
+        for j in (i + 1)..k {
+            let diff = if s.paired && s.missing == Missing::Pairwise {
+                let (x, y) = complete_pair(&samples[i].values, &samples[j].values);
+                if x.is_empty() {
+                    return Err("No Complete Pairs in Paired Data".to_string());
+                }
+                paired_diff(&x, &y, s.iterations, clevel, &mut rng)
+            } else if s.paired {
+                paired_diff(&values[i], &values[j], s.iterations, clevel, &mut rng)
+            } else {
+                unpaired_diff(&cis[i], &cis[j])
+            };
+
+            let p = p_from_ci(diff.ml, diff.mu, diff.mm, 1.0 - clevel);
+
+            pairs.push(Pairresults {
+                i,
+                j,
+                diff,
+                p,
+                adj_p: p,
+            });
+        }
+    }
+
+    let mut m = Multisample {
+        settings: s.clone(),
+        names: samples.iter().map(|g| g.name.clone()).collect(),
+        groups,
+        cis,
+        anova,
+        kruskal,
+        pairs,
+        samples: samples
+            .iter()
+            .zip(&values)
+            .map(|(g, v)| Sample {
+                name: g.name.clone(),
+                values: v.clone(),
+            })
+            .collect(),
+        missing,
+        tests: 0,
+    };
+
+    m.adjust();
+
+    Ok(m)
+}
+
+impl Comparison {
+    // Adjust every p value in the comparison as one family
+    fn adjust(&mut self) {
+        let mut family: Vec<(f64, &mut f64)> = vec![
+            (self.mean_p, &mut self.adj_mean_p),
+            (self.sd_p, &mut self.adj_sd_p),
+            (self.f_p, &mut self.adj_f_p),
+        ];
+
+        if let Some(corr) = &mut self.correlation {
+            family.push((corr.p, &mut corr.adj_p));
+            for st in [
+                &mut corr.pearson,
+                &mut corr.kendall,
+                &mut corr.slope,
+                &mut corr.intercept,
+            ] {
+                family.push((st.p, &mut st.adj_p));
+            }
+        }
+
+        if let Some(ba) = &mut self.blandaltman {
+            family.push((ba.bias.p, &mut ba.bias.adj_p));
+            family.push((ba.slope.p, &mut ba.slope.adj_p));
+        }
+
+        self.tests = adjust_family(family, self.settings.adjust);
+    }
+}
+
+impl Multisample {
+    // Name the bootstrap ANOVA, repeated measures for paired data
+    pub fn anova_name(&self) -> &'static str {
+        if self.settings.paired {
+            "Bootstrap RM ANOVA"
+        } else {
+            "Bootstrap ANOVA"
+        }
+    }
+
+    // Name the rank test and its statistic, Friedman for paired data
+    pub fn rank_test(&self) -> (&'static str, &'static str) {
+        if self.settings.paired {
+            ("Friedman", "Q")
+        } else {
+            ("Kruskal-Wallis", "H")
+        }
+    }
+
+    // Adjust the omnibus and every pairwise p value as one family
+    fn adjust(&mut self) {
+        let mut family: Vec<(f64, &mut f64)> = vec![
+            (self.anova.boot_p, &mut self.anova.adj_boot_p),
+            (self.kruskal.p, &mut self.kruskal.adj_p),
+        ];
+
+        for x in self.pairs.iter_mut() {
+            family.push((x.p, &mut x.adj_p));
+        }
+
+        self.tests = adjust_family(family, self.settings.adjust);
+    }
+}
+
+// Adjust a family of p values, writing each adjusted value back next to its raw one
+// Returns the family size, NaN p values are not tested so they are not counted
+fn adjust_family(family: Vec<(f64, &mut f64)>, method: Adjust) -> usize {
+    let raw: Vec<f64> = family.iter().map(|x| x.0).collect();
+    let adj = p_adjust(&raw, method);
+
+    for ((_, a), v) in family.into_iter().zip(adj) {
+        *a = v;
+    }
+    raw.iter().filter(|p| !p.is_nan()).count()
+}
+
+// Label the strength of a correlation coefficient
+pub fn corr_strength(r: f64) -> &'static str {
+    match r {
+        0.0 => "None",
+        r if (r - 1.0).abs() < f64::EPSILON => "Perfect Pos",
+        r if (r - -1.0).abs() < f64::EPSILON => "Perfect Neg",
+        r if r > 0.0 && r < 0.3 => "Weak Pos",
+        r if r >= 0.3 && r < 0.7 => "Moderate Pos",
+        r if r >= 0.7 && r < 1.0 => "Strong Pos",
+        r if r < 0.0 && r > -0.3 => "Weak Neg",
+        r if r <= -0.3 && r > -0.7 => "Moderate Neg",
+        r if r <= -0.7 && r > -1.00 => "Strong Neg",
+        _ => "",
+    }
+}
+
+// Calculate the descriptive stats for a sample around its bootstrapped mean and SD
+fn describe(v: &[f64], mean: f64, sd: f64, zthresh: f64) -> Descriptive {
+    let sdp = sd_pop(v, &mean);
+    let (unique_values, unique_counts) = count_unique_values(v);
+
+    let mut biggest_count = 0;
+    let mut biggest_value: f64 = 0.0;
+
+    for (i, _) in unique_values.iter().enumerate() {
+        if unique_counts[i] > biggest_count {
+            biggest_count = unique_counts[i];
+            biggest_value = unique_values[i];
+        }
+    }
+
+    Descriptive {
+        count: v.len(),
+        min: v.iter().copied().fold(f64::INFINITY, f64::min),
+        max: v.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        variance: sdp * sdp,
+        median: median(v),
+        mode: if biggest_count > 1 {
+            Some(biggest_value)
+        } else {
+            None
+        },
+        se: sd / (v.len() as f64).sqrt(),
+        skewness: skewness(v, &mean, &sd),
+        kurtosis: kurtosis(v, &mean, &sd),
+        zcount: zcount(v, zthresh),
+        unique_values,
+        unique_counts,
+    }
+}
+
+// Bland-Altman agreement, bootstrapping the subjects as (mean, difference) pairs
+fn bland_altman(
+    a_v: &[f64],
+    b_v: &[f64],
+    iterations: i32,
+    clevel: f64,
+    rng: &mut StdRng,
+) -> Blandaltman {
+    let means: Vec<f64> = a_v.iter().zip(b_v).map(|(a, b)| (a + b) / 2.0).collect();
+    let diffs = differences(a_v, b_v);
+
+    let mut boot = ci_pairs(
+        &means,
+        &diffs,
+        iterations,
+        clevel,
+        &[ba_bias, ba_lower, ba_upper, ols_slope],
+        rng,
+    )
+    .into_iter();
+
+    let bias = boot.next().unwrap();
+    // A limit of agreement has no null value to test, so it keeps its estimate and CI only
+    let mut limit = || Statresults {
+        p: f64::NAN,
+        adj_p: f64::NAN,
+        ..boot.next().unwrap()
+    };
+    let (lower, upper) = (limit(), limit());
+
+    Blandaltman {
+        bias,
+        lower,
+        upper,
+        slope: boot.next().unwrap(),
+        intercept: ols_intercept(&means, &diffs),
+        means,
+        diffs,
+    }
+}
+
+// Mean difference
+fn ba_bias(_means: &[f64], diffs: &[f64]) -> f64 {
+    mean(diffs)
+}
+
+// SDs from the bias to each limit of agreement, the usual 95% of differences whatever the CI level
+pub const LOA_Z: f64 = 1.96;
+
+// Lower limit of agreement
+fn ba_lower(_means: &[f64], diffs: &[f64]) -> f64 {
+    let m = mean(diffs);
+    m - LOA_Z * sd_sample(diffs, &m)
+}
+
+// Upper limit of agreement
+fn ba_upper(_means: &[f64], diffs: &[f64]) -> f64 {
+    let m = mean(diffs);
+    m + LOA_Z * sd_sample(diffs, &m)
+}
+
+// Paired data, also returning the sorted bootstrap distribution of the mean difference
+// The columns may hold more values than the complete pairs when missing values are handled pairwise
+fn paired_data(
+    a_v: &[f64],
+    b_v: &[f64],
+    pa: &[f64],
+    pb: &[f64],
+    iterations: i32,
+    clevel: f64,
+    rng: &mut StdRng,
+) -> (Sdmeanresults, Vec<f64>) {
+    let a = ci(a_v, iterations, clevel, rng);
+    let b = ci(b_v, iterations, clevel, rng);
+    let (c, dist) = ci_dist(&differences(pa, pb), iterations, clevel, rng);
+
+    (sdmean(&a, &b, &c), dist)
+}
+
+// Unpaired data, also returning the sorted bootstrap distribution of the mean difference
+fn unpaired_data(
+    a_v: &[f64],
+    b_v: &[f64],
+    iterations: i32,
+    clevel: f64,
+    rng: &mut StdRng,
+) -> (Sdmeanresults, Vec<f64>) {
+    let (a, mut a_dist) = ci_dist(a_v, iterations, clevel, rng);
+    let (b, b_dist) = ci_dist(b_v, iterations, clevel, rng);
+    let c = unpaired_diff(&a, &b);
+
+    // The resamples are independent, so shuffling one side pairs them up at random
+    a_dist.shuffle(rng);
+    let mut dist: Vec<f64> = b_dist.iter().zip(a_dist).map(|(b, a)| b - a).collect();
+    dist.sort_by(cmp_f64);
+
+    (sdmean(&a, &b, &c), dist)
+}
+
+// Bootstrap the differences of paired data, B - A
+fn paired_diff(
+    a_v: &[f64],
+    b_v: &[f64],
+    iterations: i32,
+    clevel: f64,
+    rng: &mut StdRng,
+) -> CIresults {
+    ci(&differences(a_v, b_v), iterations, clevel, rng)
+}
+
+// Values that are not missing
+fn present(v: &[f64]) -> Vec<f64> {
+    v.iter().copied().filter(|x| !x.is_nan()).collect()
+}
+
+// Count the missing values
+fn missing_count(v: &[f64]) -> usize {
+    v.iter().filter(|x| x.is_nan()).count()
+}
+
+// Rows of two paired columns where both values are present
+fn complete_pair(a_v: &[f64], b_v: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    a_v.iter()
+        .zip(b_v)
+        .filter(|(a, b)| !a.is_nan() && !b.is_nan())
+        .map(|(a, b)| (*a, *b))
+        .unzip()
+}
+
+// Differences of paired data, B - A
+fn differences(a_v: &[f64], b_v: &[f64]) -> Vec<f64> {
+    let mut cvalues: Vec<f64> = Vec::new();
+
+    for i in 0..a_v.len() {
+        cvalues.push(b_v[i] - a_v[i]);
+    }
+
+    cvalues
+}
+
+// Combine the CIs of unpaired data into a CI of the difference, B - A
+fn unpaired_diff(a: &CIresults, b: &CIresults) -> CIresults {
+    CIresults {
+        mu: b.mu - a.ml,
+        ml: b.ml - a.mu,
+        mm: b.mm - a.mm,
+        su: b.su - a.sl,
+        sl: b.sl - a.su,
+        sm: b.sm - a.sm,
+    }
+}
+
+// Collect the A, B and difference CIs
+fn sdmean(a: &CIresults, b: &CIresults, c: &CIresults) -> Sdmeanresults {
+    Sdmeanresults {
+        amu: a.mu,
+        aml: a.ml,
+        amm: a.mm,
+        asu: a.su,
+        asl: a.sl,
+        asm: a.sm,
+        bmu: b.mu,
+        bml: b.ml,
+        bmm: b.mm,
+        bsu: b.su,
+        bsl: b.sl,
+        bsm: b.sm,
+        dmu: c.mu,
+        dml: c.ml,
+        dmm: c.mm,
+        dsu: c.su,
+        dsl: c.sl,
+        dsm: c.sm,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_of_agreement_are_not_tested() {
+        let a = Sample {
+            name: "A".to_string(),
+            values: vec![10.0, 12.0, 11.0, 14.0, 13.0, 15.0],
+        };
+        let b = Sample {
+            name: "B".to_string(),
+            values: vec![11.0, 12.5, 11.0, 15.5, 13.0, 16.0],
+        };
+        let s = Settings {
+            paired: true,
+            iterations: 1000,
+            adjust: Adjust::Holm,
+            seed: Some(4),
+            ..Settings::default()
+        };
+        let c = compare(&a, &b, &s).unwrap();
+        let ba = c.blandaltman.as_ref().unwrap();
+
+        for st in [&ba.lower, &ba.upper] {
+            assert!(st.p.is_nan() && st.adj_p.is_nan());
+            assert!(st.l <= st.u);
+        }
+        let diffs = differences(&a.values, &b.values);
+        let m = mean(&diffs);
+        assert!((ba.upper.value - (m + LOA_Z * sd_sample(&diffs, &m))).abs() < 1e-12);
+
+        // Mean, SD and F, Spearman and the four bootstrapped correlation measures, bias and slope
+        assert_eq!(c.tests, 10);
+    }
+
+    #[test]
+    fn seeds_stay_below_2_pow_63() {
+        assert_eq!(parse_seed(" 9223372036854775807 "), Ok(MAX_SEED));
+        assert!(parse_seed("9223372036854775808").is_err());
+        let s = Settings {
+            seed: Some(u64::MAX),
+            ..Settings::default()
+        };
+        assert!(s.check().is_err());
+    }
+
+    #[test]
+    fn settings_by_name() {
+        // The CLI options and the Python keyword arguments share these names
+        assert_eq!(Adjust::from_name(" BH "), Ok(Adjust::BenjaminiHochberg));
+        assert_eq!(Adjust::from_name("holm"), Ok(Adjust::Holm));
+        assert!(Adjust::from_name("sidak").is_err());
+        assert_eq!(Missing::from_name("Pairwise"), Ok(Missing::Pairwise));
+        assert!(Missing::from_name("mean").is_err());
+    }
+}
@@ -0,0 +1,290 @@
+use crate::analysis::*;
+use crate::batch::Batch;
+use crate::document::bounds;
+use crate::report::science_pretty_format;
+use crate::stats::*;
+
+// Journal rounding, fixed decimals with trailing zeros kept, very large or small values as in the text report
+pub fn apa_number(value: f64, digits: usize) -> String {
+    if value != 0.0 && (value.abs() >= 10000.0 || value.abs() < 0.001) {
+        return science_pretty_format(value, digits);
+    }
+    let out = format!("{:.*}", digits, value);
+
+    // Rounding to zero should not leave a minus sign
+    if out
+        .trim_start_matches('-')
+        .chars()
+        .all(|c| c == '0' || c == '.')
+    {
+        out.trim_start_matches('-').to_string()
+    } else {
+        out
+    }
+}
+
+// Statistics that cannot exceed 1, like correlations, drop the leading zero
+pub fn apa_bounded(value: f64, digits: usize) -> String {
+    let out = format!("{:.*}", digits, value);
+    let out = if out.trim_start_matches('-').starts_with("0.") {
+        out.replacen("0.", ".", 1)
+    } else {
+        out
+    };
+
+    if out
+        .trim_start_matches('-')
+        .chars()
+        .all(|c| c == '0' || c == '.')
+    {
+        out.trim_start_matches('-').to_string()
+    } else {
+        out
+    }
+}
+
+// p values to three decimals without a leading zero, anything smaller as p < .001
+pub fn apa_p(p: f64) -> String {
+    if p < 0.001 {
+        "p < .001".to_string()
+    } else {
+        format!("p = {}", apa_bounded(p.min(1.0), 3))
+    }
+}
+
+// A confidence interval as 95% CI [low, high]
+fn apa_ci(s: &Settings, l: f64, u: f64) -> String {
+    apa_bounds(s, l, u, (true, true))
+}
+
+// A difference CI with only the bound on the tested side when one tailed, as in the tables
+fn apa_diff_ci(s: &Settings, l: f64, u: f64, a_larger: bool) -> String {
+    apa_bounds(s, l, u, bounds(s, a_larger))
+}
+
+// An open side of the interval is written as infinity, e.g. 95% CI [0.42, ∞]
+fn apa_bounds(s: &Settings, l: f64, u: f64, (low, high): (bool, bool)) -> String {
+    format!(
+        "{}% CI [{}, {}]",
+        science_pretty_format(s.confidence, 2),
+        if low {
+            apa_number(l, 2)
+        } else {
+            "-∞".to_string()
+        },
+        if high {
+            apa_number(u, 2)
+        } else {
+            "∞".to_string()
+        }
+    )
+}
+
+// The p value with its adjusted value, and the tails for directional tests when one tailed
+// F and omnibus tests have no direction so they never get a tail
+fn apa_tested(s: &Settings, p: f64, adj_p: f64, tailed: bool) -> String {
+    let mut out = apa_p(p);
+
+    if s.adjust != Adjust::None {
+        out.push_str(&format!(
+            ", adjusted {} ({})",
+            apa_p(adj_p),
+            s.adjust.name()
+        ));
+    }
+    if tailed && !s.two_tailed {
+        out.push_str(", one-tailed");
+    }
+    out
+}
+
+// Summary sentences of a two sample comparison in APA style
+pub fn apa_summary(c: &Comparison) -> Vec<String> {
+    let s = &c.sdmean;
+    let set = &c.settings;
+    let mut out = Vec::new();
+
+    for (name, desc, mm, ml, mu, sm) in [
+        ("A", &c.a, s.amm, s.aml, s.amu, s.asm),
+        ("B", &c.b, s.bmm, s.bml, s.bmu, s.bsm),
+    ] {
+        out.push(format!(
+            "{}: M = {}, {}, SD = {}, n = {}.",
+            name,
+            apa_number(mm, 2),
+            apa_ci(set, ml, mu),
+            apa_number(sm, 2),
+            desc.count
+        ));
+    }
+
+    out.push(format!(
+        "Mean difference (B - A): M = {}, {}, d = {}, {}.",
+        apa_number(s.dmm, 2),
+        apa_diff_ci(set, s.dml, s.dmu, s.amm > s.bmm),
+        apa_number(c.cohen_d, 2),
+        apa_tested(set, c.mean_p, c.adj_mean_p, true)
+    ));
+    out.push(format!(
+        "SD difference (B - A): {}, {}, {}.",
+        apa_number(s.dsm, 2),
+        apa_diff_ci(set, s.dsl, s.dsu, s.asm > s.bsm),
+        apa_tested(set, c.sd_p, c.adj_sd_p, true)
+    ));
+
+    // The text report gives F as the smaller variance over the larger
+    let (df1, df2) = if s.asm >= s.bsm {
+        (c.b.count, c.a.count)
+    } else {
+        (c.a.count, c.b.count)
+    };
+    out.push(format!(
+        "Variance ratio: F({}, {}) = {}, {}.",
+        df1.saturating_sub(1),
+        df2.saturating_sub(1),
+        apa_number(c.f, 2),
+        apa_tested(set, c.f_p, c.adj_f_p, false)
+    ));
+
+    if let Some(corr) = &c.correlation {
+        out.push(format!(
+            "Correlation: Spearman's ρ = {}, {}; Kendall's τb = {}, {}, {}; Pearson's r = {}, {}, {}.",
+            apa_bounded(corr.spearman, 2),
+            apa_tested(set, corr.p, corr.adj_p, true),
+            apa_bounded(corr.kendall.value, 2),
+            apa_ci(set, corr.kendall.l, corr.kendall.u),
+            apa_tested(set, corr.kendall.p, corr.kendall.adj_p, true),
+            apa_bounded(corr.pearson.value, 2),
+            apa_ci(set, corr.pearson.l, corr.pearson.u),
+            apa_tested(set, corr.pearson.p, corr.pearson.adj_p, true)
+        ));
+    }
+
+    if let Some(ba) = &c.blandaltman {
+        out.push(format!(
+            "Bland-Altman bias (B - A): {}, {}, limits of agreement [{}, {}].",
+            apa_number(ba.bias.value, 2),
+            apa_ci(set, ba.bias.l, ba.bias.u),
+            apa_number(ba.lower.value, 2),
+            apa_number(ba.upper.value, 2)
+        ));
+    }
+
+    out
+}
+
+// Summary sentences of a k sample comparison in APA style
+pub fn apa_multi_summary(m: &Multisample) -> Vec<String> {
+    let set = &m.settings;
+    let mut out = Vec::new();
+    let names = &m.names;
+
+    for (i, name) in names.iter().enumerate() {
+        out.push(format!(
+            "{}: M = {}, {}, SD = {}, n = {}.",
+            name,
+            apa_number(m.cis[i].mm, 2),
+            apa_ci(set, m.cis[i].ml, m.cis[i].mu),
+            apa_number(m.cis[i].sm, 2),
+            m.groups[i].count
+        ));
+    }
+
+    let (test, stat) = m.rank_test();
+    out.push(format!(
+        "{}: F({}, {}) = {}, {}.",
+        m.anova_name(),
+        m.anova.df1,
+        m.anova.df2,
+        apa_number(m.anova.f, 2),
+        apa_tested(set, m.anova.boot_p, m.anova.adj_boot_p, false)
+    ));
+    out.push(format!(
+        "{}: {}({}) = {}, {}.",
+        test,
+        stat,
+        m.kruskal.df,
+        apa_number(m.kruskal.h, 2),
+        apa_tested(set, m.kruskal.p, m.kruskal.adj_p, false)
+    ));
+
+    for p in &m.pairs {
+        out.push(format!(
+            "{} - {}: M = {}, {}, {}.",
+            names[p.j],
+            names[p.i],
+            apa_number(p.diff.mm, 2),
+            apa_ci(set, p.diff.ml, p.diff.mu),
+            apa_tested(set, p.p, p.adj_p, true)
+        ));
+    }
+
+    out
+}
+
+// One sentence per metric of a batch, the p values adjusted across the metrics
+pub fn apa_batch_summary(b: &Batch) -> Vec<String> {
+    let set = &b.settings;
+    let mut out = Vec::new();
+
+    for r in &b.rows {
+        let metric = &r.metric;
+        let c = match &r.result {
+            Ok(c) => c,
+            Err(e) => {
+                out.push(format!("{}: {}.", metric, e));
+                continue;
+            }
+        };
+        let names = &r.names;
+        let s = &c.sdmean;
+
+        out.push(format!(
+            "{} ({} - {}): M = {}, {}, {}.",
+            metric,
+            names[1],
+            names[0],
+            apa_number(s.dmm, 2),
+            apa_diff_ci(set, s.dml, s.dmu, s.amm > s.bmm),
+            apa_tested(set, c.mean_p, r.adj_p, true)
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_tailed_marks_only_directional_tests() {
+        let a = Sample {
+            name: "A".to_string(),
+            values: vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0],
+        };
+        let b = Sample {
+            name: "B".to_string(),
+            values: vec![3.0, 4.0, 4.0, 6.0, 7.0, 8.0, 9.0, 11.0],
+        };
+        let s = Settings {
+            two_tailed: false,
+            iterations: 1000,
+            seed: Some(1),
+            ..Settings::default()
+        };
+        let c = compare(&a, &b, &s).unwrap();
+        let lines = apa_summary(&c);
+        let line = |start: &str| lines.iter().find(|l| l.starts_with(start)).unwrap();
+
+        // B is larger so only the upper bound of B - A is shown, as in the tables
+        let (low, high) = bounds(&s, c.sdmean.amm > c.sdmean.bmm);
+        assert_eq!((low, high), (false, true));
+        let mean = line("Mean difference");
+        assert!(mean.contains(&format!("95% CI [-∞, {}]", apa_number(c.sdmean.dmu, 2))));
+        assert!(mean.ends_with(", one-tailed."));
+
+        assert!(!line("Variance ratio").contains("one-tailed"));
+        // Group CIs keep both bounds
+        assert!(line("A:").contains(&format!("[{}, ", apa_number(c.sdmean.aml, 2))));
+    }
+}
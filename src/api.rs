@@ -0,0 +1,91 @@
+use crate::analysis::*;
+use crate::apa::{apa_multi_summary, apa_summary};
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+// Define a struct for a comparison request, a and b or any number of groups, null marks a missing value
+pub struct Request {
+    pub a: Vec<Option<f64>>,
+    pub b: Vec<Option<f64>>,
+    pub groups: Vec<Vec<Option<f64>>>,
+    pub names: Vec<String>,
+    pub settings: Settings,
+}
+
+// Most bootstrap iterations one request may ask for, the server answers one request at a time
+pub const MAX_ITERATIONS: i32 = 100000;
+
+// Answer a JSON comparison request with the full result and its APA summary as JSON
+pub fn compare_json(body: &str) -> Result<String, String> {
+    let req: Request = serde_json::from_str(body).map_err(|e| format!("Bad Request: {}", e))?;
+    if req.settings.iterations > MAX_ITERATIONS {
+        return Err(format!(
+            "Iteration Count Error, at most {} per request",
+            MAX_ITERATIONS
+        ));
+    }
+    let groups = if req.groups.is_empty() {
+        vec![req.a, req.b]
+    } else {
+        req.groups
+    };
+    if groups.len() < 2 {
+        return Err("Need At Least Two Groups".to_string());
+    }
+
+    let samples: Vec<Sample> = groups
+        .into_iter()
+        .enumerate()
+        .map(|(i, g)| Sample {
+            name: req.names.get(i).cloned().unwrap_or_else(|| group_name(i)),
+            values: g.into_iter().map(|v| v.unwrap_or(f64::NAN)).collect(),
+        })
+        .collect();
+
+    // Two groups get the A / B comparison, more get the k sample one, like the reports
+    let out = if samples.len() == 2 {
+        let c = compare(&samples[0], &samples[1], &req.settings)?;
+        json!({
+            "kind": "two_sample",
+            "summary": apa_summary(&c),
+            "result": c,
+        })
+    } else {
+        let m = compare_many(&samples, &req.settings)?;
+        json!({
+            "kind": "multi_sample",
+            "summary": apa_multi_summary(&m),
+            "result": m,
+        })
+    };
+
+    serde_json::to_string_pretty(&out).map_err(|e| e.to_string())
+}
+
+// An error as a JSON body
+pub fn error_json(e: &str) -> String {
+    json!({ "error": e }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_settings_are_refused() {
+        let ask = |settings: &str| {
+            compare_json(&format!(
+                "{{\"a\": [1, 2, 3], \"b\": [2, 3, null, 5], \"settings\": {}}}",
+                settings
+            ))
+        };
+        assert!(ask("{\"confidence\": 100}").is_err());
+        assert!(ask("{\"confidence\": 0}").is_err());
+        assert!(ask("{\"iterations\": 9999000}").is_err());
+
+        let out = ask("{\"iterations\": 1000, \"seed\": 3}").unwrap();
+        assert!(out.contains("\"kind\": \"two_sample\""));
+    }
+}
@@ -0,0 +1,278 @@
+use crate::analysis::*;
+use crate::input::*;
+use crate::stats::*;
+
+#[derive(Clone, Debug)]
+// Define a struct for one metric of a long format table, its groups sorted by name so A and B do not depend on the row order
+pub struct Metric {
+    pub name: String,
+    pub samples: Vec<Sample>,
+}
+
+#[derive(Clone, Debug)]
+// Define a struct for the comparison of one metric, or why it could not be compared
+pub struct Batchrow {
+    pub metric: String,
+    pub names: Vec<String>,
+    pub result: Result<Comparison, String>,
+    pub adj_p: f64,
+}
+
+#[derive(Clone, Debug)]
+// Define a struct for a batch of metrics, the mean p values are adjusted across the metrics
+pub struct Batch {
+    pub settings: Settings,
+    pub rows: Vec<Batchrow>,
+    pub rejected: Vec<Rejected>,
+}
+
+// A metric while reading, its pair ids in order and per group the value of each pair and the ids of its rows
+type Longgroup = (String, Vec<f64>, Vec<String>);
+type Longmetric = (String, Vec<String>, Vec<Longgroup>);
+
+// Find a column by any of its usual header names
+fn find_column(t: &Table, names: &[&str]) -> Option<usize> {
+    t.headers.iter().position(|h| {
+        let h = h.trim().to_lowercase().replace([' ', '-'], "_");
+        names.contains(&h.as_str())
+    })
+}
+
+// Read a long format table of metric, group, value and an optional pair id
+pub fn read_long(
+    text: &str,
+    numbers: &Numberformat,
+) -> Result<(Vec<Metric>, Vec<Rejected>), String> {
+    let mut t = read_table(text);
+    if !numbers.auto {
+        t.format = *numbers;
+    }
+    long_metrics(&t)
+}
+
+// Split a long format table into metrics, the columns are found by header or else by position
+// Paired rows line up by pair id, a column only found by its header, or by their order within the group
+// A pair id repeated within a group is rejected rather than overwriting the earlier value
+pub fn long_metrics(t: &Table) -> Result<(Vec<Metric>, Vec<Rejected>), String> {
+    if t.headers.len() < 3 {
+        return Err("Batch Data Needs Metric, Group and Value Columns".to_string());
+    }
+
+    let metric_col = find_column(t, &["metric", "name", "variable"]).unwrap_or(0);
+    let group_col = find_column(t, &["group", "condition", "variant"]).unwrap_or(1);
+    let value_col = find_column(t, &["value", "values", "measurement"]).unwrap_or(2);
+    let pair_col = find_column(t, &["pair_id", "pair", "id", "subject"]);
+
+    let mut metrics: Vec<Longmetric> = Vec::new();
+    let mut values = t.parse_column(value_col);
+
+    for (i, r) in t.rows.iter().enumerate() {
+        let cell = |c: usize| r.get(c).map_or("", |c| c.trim());
+        let (metric, group) = (cell(metric_col), cell(group_col));
+        if metric.is_empty() || group.is_empty() {
+            continue;
+        }
+
+        let m = match metrics.iter().position(|m| m.0 == metric) {
+            Some(m) => m,
+            None => {
+                metrics.push((metric.to_string(), Vec::new(), Vec::new()));
+                metrics.len() - 1
+            }
+        };
+        let (_, pairs, groups) = &mut metrics[m];
+
+        let g = match groups.iter().position(|g| g.0 == group) {
+            Some(g) => g,
+            None => {
+                groups.push((group.to_string(), Vec::new(), Vec::new()));
+                groups.len() - 1
+            }
+        };
+
+        // Without pair ids a row pairs with the rows at the same place in the other groups
+        let id = match pair_col {
+            Some(c) if !cell(c).is_empty() => cell(c).to_string(),
+            _ => format!("#{}", groups[g].2.len()),
+        };
+        if groups[g].2.contains(&id) {
+            values.rejected.push(Rejected {
+                token: id,
+                line: t.lines.get(i).copied().unwrap_or(i + 1),
+                column: pair_col.map_or(0, |c| c + 1),
+                start: 0,
+                end: 0,
+            });
+            continue;
+        }
+        groups[g].2.push(id.clone());
+        let slot = match pairs.iter().position(|p| *p == id) {
+            Some(p) => p,
+            None => {
+                pairs.push(id);
+                pairs.len() - 1
+            }
+        };
+
+        for g in groups.iter_mut() {
+            g.1.resize(pairs.len(), f64::NAN);
+        }
+        groups[g].1[slot] = values.slots[i];
+    }
+
+    let metrics = metrics
+        .into_iter()
+        .map(|(name, pairs, mut groups)| {
+            groups.sort_by(|a, b| a.0.cmp(&b.0));
+            Metric {
+                name,
+                samples: groups
+                    .into_iter()
+                    .map(|(name, mut values, _)| {
+                        values.resize(pairs.len(), f64::NAN);
+                        Sample { name, values }
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    Ok((metrics, values.rejected))
+}
+
+// Compare every metric of a long format table, keeping the rejected value cells for the report
+pub fn batch(t: &Table, s: &Settings) -> Result<Batch, String> {
+    let (metrics, rejected) = long_metrics(t)?;
+    let mut b = compare_batch(&metrics, s)?;
+
+    b.rejected = rejected;
+    Ok(b)
+}
+
+// Compare the two groups of every metric, each with its own seed drawn from the batch seed
+// The mean difference p values are adjusted across the metrics with the chosen method
+pub fn compare_batch(metrics: &[Metric], s: &Settings) -> Result<Batch, String> {
+    s.check()?;
+    if metrics.is_empty() {
+        return Err("No Metrics Found".to_string());
+    }
+
+    let seed = draw_seed(s.seed);
+
+    let mut rows: Vec<Batchrow> = metrics
+        .iter()
+        .enumerate()
+        .map(|(i, m)| {
+            let settings = Settings {
+                adjust: Adjust::None,
+                seed: Some(seed.wrapping_add(i as u64) & MAX_SEED),
+                ..s.clone()
+            };
+            let result = match m.samples.as_slice() {
+                [a, b] => compare(a, b, &settings),
+                _ => Err(format!("Needs Two Groups, Found {}", m.samples.len())),
+            };
+            Batchrow {
+                metric: m.name.clone(),
+                names: m.samples.iter().map(|g| g.name.clone()).collect(),
+                result,
+                adj_p: f64::NAN,
+            }
+        })
+        .collect();
+
+    let p: Vec<f64> = rows
+        .iter()
+        .map(|r| r.result.as_ref().map_or(f64::NAN, |c| c.mean_p))
+        .collect();
+    for (r, adj) in rows.iter_mut().zip(p_adjust(&p, s.adjust)) {
+        r.adj_p = adj;
+    }
+
+    Ok(Batch {
+        settings: Settings {
+            seed: Some(seed),
+            ..s.clone()
+        },
+        rows,
+        rejected: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_metrics_pair_by_id() {
+        let t = read_table(
+            "metric,group,value,pair_id\n\
+             load,new,12,s2\n\
+             load,old,10,s1\n\
+             load,new,11,s1\n\
+             load,old,9,s2\n\
+             load,old,8,s3\n\
+             size,b,x,1\n\
+             size,a,5,1\n",
+        );
+        let (metrics, rejected) = long_metrics(&t).unwrap();
+
+        assert_eq!(metrics.len(), 2);
+        let load = &metrics[0];
+        assert_eq!(load.name, "load");
+        // Groups are sorted, each pair id keeps its slot and s3 has no new value
+        assert_eq!(load.samples[0].name, "new");
+        assert_eq!(load.samples[1].name, "old");
+        assert_eq!(load.samples[1].values, vec![9.0, 10.0, 8.0]);
+        assert_eq!(&load.samples[0].values[..2], &[12.0, 11.0]);
+        assert!(load.samples[0].values[2].is_nan());
+
+        let size = &metrics[1];
+        assert_eq!(size.samples[0].name, "a");
+        assert_eq!(size.samples[0].values, vec![5.0]);
+        assert_eq!(rejected.len(), 1);
+        assert_eq!((rejected[0].token.as_str(), rejected[0].line), ("x", 7));
+    }
+
+    #[test]
+    fn long_metrics_pair_only_by_header() {
+        // A 4th column of notes is not a pair id, rows pair by their order
+        let t = read_table(
+            "metric,group,value,note
+             load,a,1,same
+             load,b,2,same
+             load,a,3,same
+             load,b,4,same
+",
+        );
+        let (metrics, rejected) = long_metrics(&t).unwrap();
+        assert!(rejected.is_empty());
+        assert_eq!(metrics[0].samples[0].values, vec![1.0, 3.0]);
+        assert_eq!(metrics[0].samples[1].values, vec![2.0, 4.0]);
+    }
+
+    #[test]
+    fn long_metrics_rejects_repeated_pair_id() {
+        let t = read_table(
+            "metric,group,value,pair_id
+             load,a,1,s1
+             load,b,2,s1
+             load,a,3,s1
+             load,b,4,s2
+",
+        );
+        let (metrics, rejected) = long_metrics(&t).unwrap();
+        // The first value of s1 in a is kept, the repeat is reported by line and column
+        assert_eq!(metrics[0].samples[0].values[0], 1.0);
+        assert!(metrics[0].samples[0].values[1].is_nan());
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(
+            (
+                rejected[0].token.as_str(),
+                rejected[0].line,
+                rejected[0].column
+            ),
+            ("s1", 4, 4)
+        );
+    }
+}
@@ -0,0 +1,552 @@
+// Headless front end, prints the report and optionally writes the charts to files
+use bootstrapcicalculator::analysis::*;
+use bootstrapcicalculator::api::{compare_json, error_json};
+use bootstrapcicalculator::batch::batch;
+use bootstrapcicalculator::document::*;
+use bootstrapcicalculator::export::*;
+use bootstrapcicalculator::input::*;
+use bootstrapcicalculator::plot::*;
+use bootstrapcicalculator::prefs::{find_preset, read_preset};
+use bootstrapcicalculator::report::*;
+use bootstrapcicalculator::stats::Adjust;
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+
+const USAGE: &str = "Usage: bootstrapci [options] [FILE ...]
+       bootstrapci serve [--host ADDR] [--port N]
+
+Each file holds one group of comma or newline separated values, - reads stdin.
+A single file, or stdin when no files are given, holds all the groups either
+split by lines of three or more dashes or as columns, e.g.
+
+  cat results.tsv | bootstrapci --paired
+
+Spreadsheets (xlsx, xls, ods) give one group per column of the sheet.
+Lines starting with # are comments and a first line of words names the group
+or the columns. Numbers are read as plain comma separated values with a decimal
+point unless --auto-format or one of the format options is given.
+
+Options:
+  --preset NAME        Start from a saved preset or a preset file, later
+                       options override it
+  --paired             Paired or correlated data
+  --one-tailed         One tailed tests (default two tailed)
+  --cl N               Confidence level in percent (default 95)
+  --iterations K       Bootstrap iterations in thousands (default 10)
+  --zthresh Z          Z score outlier threshold (default 3.0)
+  --adjust METHOD      none, bonferroni, holm or bh (default none)
+  --missing METHOD     Paired rows with NA, listwise or pairwise (default listwise)
+  --seed N             Random seed, repeats an earlier run (default a new seed)
+  --sheet NAME         Spreadsheet sheet to read (default the first with data)
+  --range RANGE        Spreadsheet cells to read, e.g. B2:D40
+  --batch              One long format input of metric, group, value and an
+                       optional column headed pair_id, compares the two
+                       groups of every metric and adjusts the p values
+                       across the metrics with --adjust, e.g. --adjust bh
+  --apa                Print an APA style summary instead of the full report
+  --report FILE        Also write the report as Markdown (.md), HTML (.html),
+                       PDF (.pdf), LaTeX tables (.tex) or tab separated
+                       tables (.tsv)
+  --plots DIR          Write every chart into DIR
+  --format FORMAT      Chart format, svg or png (default svg)
+  --strict             Stop if any token is not a number
+  --decimal SEP        Decimal mark, point or comma
+  --thousands SEP      Thousands separator, none, point, comma or apostrophe
+  --delimiter SEP      Value delimiter, comma, semicolon, tab or newline
+  --auto-format        Detect the number format once across all the inputs
+                       and print what was found
+  -h, --help           Show this help
+
+serve answers comparisons over HTTP, on 127.0.0.1:8080 unless told otherwise.
+POST /compare a JSON object with the arrays a and b, or groups as an array of
+arrays, plus optional names and settings (paired, two_tailed, confidence,
+iterations up to 100000, zthresh, adjust, missing, seed), null for a missing
+value:
+
+  curl -d '{\"a\": [1, 2, 3], \"b\": [2, 3, 5]}' localhost:8080/compare
+
+The reply is the full result with an APA summary as JSON, GET /health checks
+the server is up.";
+
+// Largest request body the server reads
+const BODY_LIMIT: u64 = 16 << 20;
+
+// Define a struct for the command line options
+struct Options {
+    settings: Settings,
+    files: Vec<PathBuf>,
+    plots: Option<PathBuf>,
+    report: Option<PathBuf>,
+    apa: bool,
+    format: String,
+    strict: bool,
+    numbers: Numberformat,
+    sheet: String,
+    range: String,
+    batch: bool,
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.first().is_some_and(|a| a == "serve") {
+        let addr = match serve_args(&args[1..]) {
+            Ok(a) => a,
+            Err(e) => {
+                eprintln!("{}\n\n{}", e, USAGE);
+                exit(2);
+            }
+        };
+        if let Err(e) = serve(&addr) {
+            eprintln!("{}", e);
+            exit(1);
+        }
+        return;
+    }
+
+    let opts = match parse_args(args) {
+        Ok(o) => o,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            exit(2);
+        }
+    };
+
+    if let Err(e) = run(&opts) {
+        eprintln!("{}", e);
+        exit(1);
+    }
+}
+
+fn run(o: &Options) -> Result<(), String> {
+    if o.batch {
+        return run_batch(o);
+    }
+    let mut samples: Vec<Sample> = Vec::new();
+    let (groups, detected) = read_groups(o)?;
+    if let Some(f) = detected {
+        eprintln!("Number Format: \t{} (detected)", f.describe());
+    }
+
+    if groups.len() < 2 {
+        return Err(
+            "Need at least two groups, split one input with --- lines or give two columns"
+                .to_string(),
+        );
+    }
+
+    for (i, (source, header, parsed)) in groups.into_iter().enumerate() {
+        if !header.is_empty() {
+            eprintln!("Data {}: \t{}", group_name(i), header);
+        }
+
+        // Rejected tokens go to stderr so the report stays clean
+        if !parsed.suspicious.is_empty() {
+            eprint!("{}", suspicious_tokens(&source, &parsed.suspicious));
+        }
+        if !parsed.rejected.is_empty() {
+            eprint!("{}", rejected_tokens(&source, &parsed.rejected));
+            if o.strict {
+                return Err(format!("{}: Strict Input, Tokens Rejected", source));
+            }
+        }
+
+        // Paired data keeps a slot for every cell so missing values do not shift the rows
+        let values = if o.settings.paired {
+            parsed.slots
+        } else {
+            parsed.values
+        };
+        if values.iter().all(|v| v.is_nan()) {
+            return Err(format!("{}: No values found", source));
+        }
+        samples.push(Sample {
+            name: group_name(i),
+            values,
+        });
+    }
+
+    // Two groups get the full A / B report, more get the k sample report
+    let (out, charts, document) = if samples.len() == 2 {
+        let c = compare(&samples[0], &samples[1], &o.settings)?;
+        (two_sample(&c), charts(&c), comparison_document(&c))
+    } else {
+        let m = compare_many(&samples, &o.settings)?;
+        (multi_sample(&m), multi_charts(&m), multi_document(&m))
+    };
+
+    if o.apa {
+        println!("{}", document.summary.join("\n"));
+    } else {
+        print!("{}", out);
+    }
+
+    if let Some(path) = &o.report {
+        save_report(&document, &charts, path)?;
+    }
+
+    if let Some(dir) = &o.plots {
+        std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        for chart in &charts {
+            let path = dir.join(format!("{}.{}", chart_file_name(chart), o.format));
+            save_chart(chart, &path, EXPORT_W, EXPORT_H)?;
+        }
+    }
+
+    Ok(())
+}
+
+// The address to serve on, localhost unless a host is given
+fn serve_args(args: &[String]) -> Result<String, String> {
+    let mut host = "127.0.0.1".to_string();
+    let mut port = "8080".to_string();
+    let mut args = args.iter();
+
+    while let Some(a) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or(format!("Missing value for {}", a))
+        };
+        match a.as_str() {
+            "--host" => host = value()?,
+            "--port" => {
+                port = value()?;
+                port.parse::<u16>()
+                    .map_err(|_| format!("Bad port {}", port))?;
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                exit(0);
+            }
+            f => return Err(format!("Unknown option {}", f)),
+        }
+    }
+    Ok(format!("{}:{}", host, port))
+}
+
+// Answer comparison requests over HTTP one at a time until the process is stopped
+fn serve(addr: &str) -> Result<(), String> {
+    let server = tiny_http::Server::http(addr).map_err(|e| format!("{}: {}", addr, e))?;
+    eprintln!("Listening on http://{}", addr);
+
+    for mut req in server.incoming_requests() {
+        let path = req.url().split('?').next().unwrap_or("").to_string();
+        let (status, body) = match (req.method(), path.as_str()) {
+            (tiny_http::Method::Post, "/compare") => {
+                let mut body = String::new();
+                match req
+                    .as_reader()
+                    .take(BODY_LIMIT + 1)
+                    .read_to_string(&mut body)
+                {
+                    Err(e) => (400, error_json(&e.to_string())),
+                    Ok(n) if n as u64 > BODY_LIMIT => (413, error_json("Request Too Large")),
+                    // A panic fails this request only, the server keeps answering
+                    Ok(_) => match std::panic::catch_unwind(|| compare_json(&body)) {
+                        Ok(Ok(out)) => (200, out),
+                        Ok(Err(e)) => (400, error_json(&e)),
+                        Err(_) => (500, error_json("Internal Error")),
+                    },
+                }
+            }
+            (tiny_http::Method::Get, "/health") => (200, "{\"status\":\"ok\"}".to_string()),
+            (_, "/compare" | "/health") => (405, error_json("Method Not Allowed")),
+            _ => (404, error_json("Not Found")),
+        };
+
+        let response = tiny_http::Response::from_string(body)
+            .with_status_code(status)
+            .with_header(
+                tiny_http::Header::from_bytes("Content-Type", "application/json").unwrap(),
+            );
+        if let Err(e) = req.respond(response) {
+            eprintln!("{}", e);
+        }
+    }
+    Ok(())
+}
+
+// Compare every metric of one long format input and print the summary
+fn run_batch(o: &Options) -> Result<(), String> {
+    if o.files.len() > 1 {
+        return Err("Batch mode reads a single input".to_string());
+    }
+    if o.plots.is_some() {
+        return Err("Batch mode has no charts, leave out --plots".to_string());
+    }
+
+    let f = o
+        .files
+        .first()
+        .cloned()
+        .unwrap_or_else(|| PathBuf::from("-"));
+    let (source, table) = if is_workbook(&f) {
+        read_sheet(&f, o)?
+    } else {
+        let (source, text) = read_input(&f)?;
+        let mut table = read_table(&text);
+        if o.numbers.auto {
+            eprintln!("Number Format: \t{} (detected)", table.format.describe());
+        } else {
+            table.format = o.numbers;
+        }
+        (source, table)
+    };
+
+    let b = batch(&table, &o.settings)?;
+    if !b.rejected.is_empty() {
+        eprint!("{}", rejected_tokens(&source, &b.rejected));
+        if o.strict {
+            return Err(format!("{}: Strict Input, Tokens Rejected", source));
+        }
+    }
+
+    let document = batch_document(&b);
+    if o.apa {
+        println!("{}", document.summary.join("\n"));
+    } else {
+        print!("{}", batch_report(&b));
+    }
+
+    if let Some(path) = &o.report {
+        save_report(&document, &[], path)?;
+    }
+
+    Ok(())
+}
+
+// Read every input into groups of source label, header and parsed values, plus the number format if it was detected
+type Groups = Vec<(String, String, Parsed)>;
+fn read_groups(o: &Options) -> Result<(Groups, Option<Numberformat>), String> {
+    let files = if o.files.is_empty() {
+        vec![PathBuf::from("-")]
+    } else {
+        o.files.clone()
+    };
+    let mut out = Vec::new();
+    // Block bodies are parsed at the end so one detected format covers all of them
+    let mut bodies = Vec::new();
+    let mut detected = None;
+
+    for f in &files {
+        if is_workbook(f) {
+            let (source, table) = read_sheet(f, o)?;
+            out.extend(columns(&source, &table));
+            continue;
+        }
+        let (source, text) = read_input(f)?;
+
+        // One input on its own carries every group, auto keeps the format the table detected
+        if files.len() == 1 && !has_blocks(&text) {
+            let mut table = read_table(&text);
+            if o.numbers.auto {
+                detected = Some(table.format);
+            } else {
+                table.format = o.numbers;
+            }
+            out.extend(columns(&source, &table));
+            continue;
+        }
+
+        let blocks = split_blocks(&text);
+        let count = blocks.len();
+        for (b, block) in blocks.into_iter().enumerate() {
+            // Nothing after a final separator line is not a group
+            if b + 1 == count && b > 0 && block.trim().is_empty() {
+                continue;
+            }
+            let (header, body) = take_header(&block);
+            let label = if count > 1 {
+                format!("{} block {}", source, b + 1)
+            } else {
+                source.clone()
+            };
+            bodies.push((out.len(), body));
+            out.push((label, header, Parsed::default()));
+        }
+    }
+
+    if !bodies.is_empty() {
+        let texts: Vec<&String> = bodies.iter().map(|(_, b)| b).collect();
+        let numbers = o.numbers.resolve_all(&texts);
+        if o.numbers.auto {
+            detected = Some(numbers);
+        }
+        for (i, body) in &bodies {
+            out[*i].2 = csv_parse(body, &numbers);
+        }
+    }
+
+    Ok((out, detected))
+}
+
+// One group per column of a table, generated column names are not headers
+fn columns(source: &str, table: &Table) -> Vec<(String, String, Parsed)> {
+    table
+        .headers
+        .iter()
+        .enumerate()
+        .map(|(c, h)| {
+            let header = if h.starts_with("Column ") {
+                String::new()
+            } else {
+                h.clone()
+            };
+            (
+                format!("{} column {}", source, c + 1),
+                header,
+                table.parse_column(c),
+            )
+        })
+        .collect()
+}
+
+// Read the chosen sheet and range of a workbook
+fn read_sheet(f: &Path, o: &Options) -> Result<(String, Table), String> {
+    let sheets = read_workbook(f)?;
+    let sheet = if o.sheet.is_empty() {
+        sheets
+            .iter()
+            .find(|s| s.table(&o.range).is_ok_and(|t| t.has_numbers()))
+            .ok_or_else(|| format!("{}: No Data Found", f.display()))?
+    } else {
+        sheets
+            .iter()
+            .find(|s| s.name == o.sheet)
+            .ok_or_else(|| format!("{}: No sheet named {}", f.display(), o.sheet))?
+    };
+
+    let table = sheet.table(&o.range)?;
+    Ok((format!("{} {}", f.display(), sheet.name), table))
+}
+
+// Read a file, or stdin for -
+fn read_input(f: &Path) -> Result<(String, String), String> {
+    if f.as_os_str() == "-" {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|e| format!("stdin: {}", e))?;
+        return Ok(("stdin".to_string(), text));
+    }
+
+    let text = std::fs::read_to_string(f).map_err(|e| format!("{}: {}", f.display(), e))?;
+    Ok((f.display().to_string(), text))
+}
+
+fn parse_args(args: Vec<String>) -> Result<Options, String> {
+    let mut o = Options {
+        settings: Settings::default(),
+        files: Vec::new(),
+        plots: None,
+        report: None,
+        apa: false,
+        format: "svg".to_string(),
+        strict: false,
+        numbers: Numberformat::default(),
+        sheet: String::new(),
+        range: String::new(),
+        batch: false,
+    };
+
+    let mut args = args.into_iter();
+    while let Some(a) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", name))
+        };
+
+        match a.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                exit(0);
+            }
+            "--paired" => o.settings.paired = true,
+            "--strict" => o.strict = true,
+            "--apa" => o.apa = true,
+            "--batch" => o.batch = true,
+            "--auto-format" => o.numbers.auto = true,
+            "--one-tailed" => o.settings.two_tailed = false,
+            "--cl" => o.settings.confidence = number(&value(&a)?, "Confidence Level Error")?,
+            "--iterations" => {
+                o.settings.iterations = value(&a)?
+                    .parse::<i32>()
+                    .ok()
+                    .and_then(|v| v.checked_mul(1000))
+                    .ok_or("Iteration Count Error".to_string())?
+            }
+            "--preset" => {
+                let v = value(&a)?;
+                let preset = if Path::new(&v).is_file() {
+                    read_preset(Path::new(&v))?
+                } else {
+                    find_preset(&v).ok_or(format!("Unknown preset {}", v))?
+                };
+                o.settings = preset.settings;
+                o.strict = preset.strict;
+                o.numbers = preset.numbers;
+            }
+            "--seed" => o.settings.seed = Some(parse_seed(&value(&a)?)?),
+            "--zthresh" => o.settings.zthresh = number(&value(&a)?, "Z Threshold Error")?,
+            "--adjust" => o.settings.adjust = Adjust::from_name(&value(&a)?)?,
+            "--missing" => o.settings.missing = Missing::from_name(&value(&a)?)?,
+            "--decimal" => {
+                o.numbers.auto = false;
+                o.numbers.decimal = match value(&a)?.to_lowercase().as_str() {
+                    "point" | "." => '.',
+                    "comma" | "," => ',',
+                    m => return Err(format!("Unknown decimal mark {}", m)),
+                }
+            }
+            "--thousands" => {
+                o.numbers.auto = false;
+                o.numbers.thousands = match value(&a)?.to_lowercase().as_str() {
+                    "none" => None,
+                    "point" | "." => Some('.'),
+                    "comma" | "," => Some(','),
+                    "apostrophe" | "'" => Some('\''),
+                    m => return Err(format!("Unknown thousands separator {}", m)),
+                }
+            }
+            "--delimiter" => {
+                o.numbers.auto = false;
+                o.numbers.delimiter = match value(&a)?.to_lowercase().as_str() {
+                    "comma" | "," => ',',
+                    "semicolon" | ";" => ';',
+                    "tab" => '\t',
+                    "newline" => '\n',
+                    m => return Err(format!("Unknown delimiter {}", m)),
+                }
+            }
+            "--sheet" => o.sheet = value(&a)?,
+            "--range" => {
+                o.range = value(&a)?;
+                parse_range(&o.range)?;
+            }
+            "--report" => o.report = Some(PathBuf::from(value(&a)?)),
+            "--plots" => o.plots = Some(PathBuf::from(value(&a)?)),
+            "--format" => {
+                o.format = value(&a)?.to_lowercase();
+                if o.format != "svg" && o.format != "png" {
+                    return Err("Export Format Error, use svg or png".to_string());
+                }
+            }
+            f if f.starts_with("--") => return Err(format!("Unknown option {}", f)),
+            f => o.files.push(PathBuf::from(f)),
+        }
+    }
+
+    if o.files.iter().filter(|f| f.as_os_str() == "-").count() > 1 {
+        return Err("Stdin can only be read once".to_string());
+    }
+    o.numbers.check()?;
+
+    Ok(o)
+}
+
+fn number(s: &str, err: &str) -> Result<f64, String> {
+    s.parse::<f64>().map_err(|_| err.to_string())
+}
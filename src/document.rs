@@ -0,0 +1,798 @@
+use crate::analysis::*;
+use crate::apa::*;
+use crate::batch::Batch;
+use crate::export::{svg, EXPORT_H, EXPORT_W};
+use crate::pdf::pdf;
+use crate::plot::Chart;
+use crate::report::science_pretty_format;
+use crate::stats::*;
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+// Define a struct for one table of a report, the first column names each row
+pub struct Grid {
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+// Define a struct for one titled section of a report
+pub struct Section {
+    pub title: String,
+    pub grid: Grid,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+// Define a struct for a whole report, laid out as tables so every format shows the same thing
+pub struct Document {
+    pub title: String,
+    pub summary: Vec<String>,
+    pub sections: Vec<Section>,
+}
+
+impl Grid {
+    fn new(header: &[&str]) -> Grid {
+        Grid {
+            header: header.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    fn row<S: AsRef<str>>(&mut self, cells: &[S]) {
+        self.rows
+            .push(cells.iter().map(|c| c.as_ref().to_string()).collect());
+    }
+}
+
+impl Document {
+    fn section(&mut self, title: &str, grid: Grid) {
+        self.sections.push(Section {
+            title: title.to_string(),
+            grid,
+        });
+    }
+}
+
+// Lay out a two sample comparison as a report
+pub fn comparison_document(c: &Comparison) -> Document {
+    let mut doc = Document {
+        title: "Bootstrap Comparison of A and B".to_string(),
+        summary: apa_summary(c),
+        sections: Vec::new(),
+    };
+    let s = &c.sdmean;
+    let set = &c.settings;
+    let f = |v: f64| science_pretty_format(v, 6);
+
+    doc.section("Parameters", parameters(set, c.tests));
+
+    // Descriptive statistics side by side, Diff is always B - A
+    let mut g = Grid::new(&["Metric", "A", "B", "Diff"]);
+    g.row(&[
+        "Count".to_string(),
+        c.a.count.to_string(),
+        c.b.count.to_string(),
+        String::new(),
+    ]);
+    if c.missing.counts.iter().any(|n| *n > 0) {
+        let n = |i: usize| c.missing.counts.get(i).copied().unwrap_or(0).to_string();
+        g.row(&["Missing".to_string(), n(0), n(1), String::new()]);
+        if set.paired {
+            g.row(&[
+                format!("Dropped Rows ({})", set.missing.name()),
+                c.missing.dropped.to_string(),
+                c.missing.dropped.to_string(),
+                String::new(),
+            ]);
+        }
+    }
+    let both = |g: &mut Grid, name: &str, a: f64, b: f64| {
+        g.row(&[name.to_string(), f(a), f(b), f(b - a)]);
+    };
+    both(&mut g, "Min", c.a.min, c.b.min);
+    both(&mut g, "Max", c.a.max, c.b.max);
+    g.row(&["Mean".to_string(), f(s.amm), f(s.bmm), f(s.dmm)]);
+    g.row(&["SD".to_string(), f(s.asm), f(s.bsm), f(s.dsm)]);
+    both(&mut g, "Variance", c.a.variance, c.b.variance);
+    both(&mut g, "Median", c.a.median, c.b.median);
+    let mode = |m: Option<f64>| m.map_or("NONE".to_string(), f);
+    g.row(&[
+        "Mode".to_string(),
+        mode(c.a.mode),
+        mode(c.b.mode),
+        String::new(),
+    ]);
+    g.row(&["SE".to_string(), f(c.a.se), f(c.b.se), String::new()]);
+    let f3 = |v: f64| science_pretty_format(v, 3);
+    g.row(&[
+        "Skewness".to_string(),
+        f3(c.a.skewness),
+        f3(c.b.skewness),
+        String::new(),
+    ]);
+    g.row(&[
+        "Kurtosis".to_string(),
+        f3(c.a.kurtosis),
+        f3(c.b.kurtosis),
+        String::new(),
+    ]);
+    let z = |d: &Descriptive, plus: bool| {
+        let (n, pc) = if plus {
+            (d.zcount.pluscount, d.zcount.pluspercent)
+        } else {
+            (d.zcount.minuscount, d.zcount.minuspercent)
+        };
+        format!("{} ({}%)", n, science_pretty_format(pc, 1))
+    };
+    g.row(&[
+        "+Z Count".to_string(),
+        z(&c.a, true),
+        z(&c.b, true),
+        String::new(),
+    ]);
+    g.row(&[
+        "-Z Count".to_string(),
+        z(&c.a, false),
+        z(&c.b, false),
+        String::new(),
+    ]);
+    doc.section("Descriptive Statistics", g);
+
+    // One tailed tests only show the bound of the difference on the tested side
+    let mut g = Grid::new(&["Metric", "A", "B", "Diff"]);
+    let (mean_low, mean_high) = bounds(set, s.amm > s.bmm);
+    let (sd_low, sd_high) = bounds(set, s.asm > s.bsm);
+    let side = |show: bool, v: f64| if show { f(v) } else { String::new() };
+    g.row(&[
+        "Mean CI Low".to_string(),
+        f(s.aml),
+        f(s.bml),
+        side(mean_low, s.dml),
+    ]);
+    g.row(&["Mean".to_string(), f(s.amm), f(s.bmm), f(s.dmm)]);
+    g.row(&[
+        "Mean CI High".to_string(),
+        f(s.amu),
+        f(s.bmu),
+        side(mean_high, s.dmu),
+    ]);
+    g.row(&[
+        "SD CI Low".to_string(),
+        f(s.asl),
+        f(s.bsl),
+        side(sd_low, s.dsl),
+    ]);
+    g.row(&["SD".to_string(), f(s.asm), f(s.bsm), f(s.dsm)]);
+    g.row(&[
+        "SD CI High".to_string(),
+        f(s.asu),
+        f(s.bsu),
+        side(sd_high, s.dsu),
+    ]);
+    doc.section(
+        &format!(
+            "{}% Confidence Intervals",
+            science_pretty_format(set.confidence, 2)
+        ),
+        g,
+    );
+
+    let mut g = test_grid(set);
+    test_row(
+        &mut g,
+        set,
+        "Mean Diff",
+        f(s.dmm),
+        c.mean_p,
+        c.adj_mean_p,
+        verdict(c.mean_p, set.clevel(), s.amm, s.bmm, "A", "B"),
+    );
+    test_row(
+        &mut g,
+        set,
+        "SD Diff",
+        f(s.dsm),
+        c.sd_p,
+        c.adj_sd_p,
+        verdict(c.sd_p, set.clevel(), s.asm, s.bsm, "A", "B"),
+    );
+    test_row(
+        &mut g,
+        set,
+        "F-Test",
+        science_pretty_format(c.f, 4),
+        c.f_p,
+        c.adj_f_p,
+        significance(c.f_p, set.clevel()),
+    );
+    doc.section("Hypothesis Tests", g);
+
+    let mut g = Grid::new(&["Metric", "Value"]);
+    g.row(&["Cohen's d", &science_pretty_format(c.cohen_d, 2)]);
+    let pc = |a: f64, b: f64| format!("{}%", science_pretty_format(per_change(&a, &b), 1));
+    g.row(&["Mean % Change", &pc(s.amm, s.bmm)]);
+    g.row(&["SD % Change", &pc(s.asm, s.bsm)]);
+    g.row(&["Median % Change", &pc(c.a.median, c.b.median)]);
+    doc.section("Effect Size", g);
+
+    // Paired data adds correlation and agreement
+    if let Some(corr) = &c.correlation {
+        let mut g = stat_grid(set);
+        let mut spearman = vec![
+            "Spearman's ρ".to_string(),
+            science_pretty_format(corr.spearman, 2),
+            String::new(),
+            String::new(),
+            science_pretty_format(corr.p, 3),
+        ];
+        if set.adjust != Adjust::None {
+            spearman.push(science_pretty_format(corr.adj_p, 3));
+        }
+        spearman.push(format!(
+            "{}, {}",
+            corr_strength(corr.spearman),
+            significance(corr.p, set.clevel())
+        ));
+        g.row(&spearman);
+        let mut kendall = stat_cells(set, "Kendall's τb", &corr.kendall, 2);
+        kendall.push(format!(
+            "{}, {}",
+            corr_strength(corr.kendall.value),
+            significance(corr.kendall.p, set.clevel())
+        ));
+        g.row(&kendall);
+        stat_row(&mut g, set, "Pearson r", &corr.pearson, 3);
+        stat_row(&mut g, set, "Slope B~A", &corr.slope, 6);
+        stat_row(&mut g, set, "Intercept", &corr.intercept, 6);
+        let mut r2 = vec![
+            "R²".to_string(),
+            science_pretty_format(corr.r2, 3),
+            String::new(),
+            String::new(),
+            String::new(),
+        ];
+        if set.adjust != Adjust::None {
+            r2.push(String::new());
+        }
+        r2.push(String::new());
+        g.row(&r2);
+        doc.section("Correlation", g);
+    }
+
+    if let Some(ba) = &c.blandaltman {
+        let mut g = stat_grid(set);
+        stat_row(&mut g, set, "Bias", &ba.bias, 6);
+        // Limits of agreement are estimates without a test
+        for (name, st) in [("Lower LoA", &ba.lower), ("Upper LoA", &ba.upper)] {
+            let mut r = vec![
+                name.to_string(),
+                science_pretty_format(st.value, 6),
+                science_pretty_format(st.l, 6),
+                science_pretty_format(st.u, 6),
+            ];
+            r.resize(g.header.len(), String::new());
+            g.row(&r);
+        }
+        stat_row(&mut g, set, "Prop Bias", &ba.slope, 6);
+        doc.section("Bland-Altman (B - A)", g);
+    }
+
+    doc.section("Unique A Value Counts", unique_grid(&c.a));
+    doc.section("Unique B Value Counts", unique_grid(&c.b));
+
+    doc
+}
+
+// Lay out a k sample comparison as a report
+pub fn multi_document(m: &Multisample) -> Document {
+    let mut doc = Document {
+        title: format!("Bootstrap Comparison of {} Groups", m.names.len()),
+        summary: apa_multi_summary(m),
+        sections: Vec::new(),
+    };
+    let set = &m.settings;
+    let f = |v: f64| science_pretty_format(v, 6);
+
+    doc.section("Parameters", parameters(set, m.tests));
+
+    let mut header = vec!["Metric"];
+    header.extend(m.names.iter().map(|n| n.as_str()));
+    let mut g = Grid::new(&header);
+    let mut row = |name: &str, cell: &dyn Fn(usize) -> String| {
+        let mut r = vec![name.to_string()];
+        r.extend((0..m.names.len()).map(cell));
+        g.row(&r);
+    };
+    row("Count", &|i| m.groups[i].count.to_string());
+    if m.missing.counts.iter().any(|n| *n > 0) {
+        row("Missing", &|i| {
+            m.missing.counts.get(i).copied().unwrap_or(0).to_string()
+        });
+    }
+    row("Min", &|i| f(m.groups[i].min));
+    row("Max", &|i| f(m.groups[i].max));
+    row("Mean CI Low", &|i| f(m.cis[i].ml));
+    row("Mean", &|i| f(m.cis[i].mm));
+    row("Mean CI High", &|i| f(m.cis[i].mu));
+    row("SD CI Low", &|i| f(m.cis[i].sl));
+    row("SD", &|i| f(m.cis[i].sm));
+    row("SD CI High", &|i| f(m.cis[i].su));
+    row("Median", &|i| f(m.groups[i].median));
+    doc.section("Descriptive Statistics", g);
+
+    let (test, stat) = m.rank_test();
+    let mut g = test_grid(set);
+    test_row(
+        &mut g,
+        set,
+        &format!("{} F({}, {})", m.anova_name(), m.anova.df1, m.anova.df2),
+        science_pretty_format(m.anova.f, 4),
+        m.anova.boot_p,
+        m.anova.adj_boot_p,
+        significance(m.anova.boot_p, set.alpha()),
+    );
+    test_row(
+        &mut g,
+        set,
+        &format!("{} {}({})", test, stat, m.kruskal.df),
+        science_pretty_format(m.kruskal.h, 4),
+        m.kruskal.p,
+        m.kruskal.adj_p,
+        significance(m.kruskal.p, set.alpha()),
+    );
+    doc.section("Omnibus Tests", g);
+
+    let mut header = vec![
+        "Pair",
+        "CI Low Diff",
+        "Mean Diff",
+        "CI High Diff",
+        "p-Value",
+    ];
+    if set.adjust != Adjust::None {
+        header.push("Adj p-Value");
+    }
+    header.push("Result");
+    let mut g = Grid::new(&header);
+    for p in &m.pairs {
+        let (a, b) = (&m.names[p.i], &m.names[p.j]);
+        let mut r = vec![
+            format!("{} - {}", b, a),
+            f(p.diff.ml),
+            f(p.diff.mm),
+            f(p.diff.mu),
+            science_pretty_format(p.p, 3),
+        ];
+        if set.adjust != Adjust::None {
+            r.push(science_pretty_format(p.adj_p, 3));
+        }
+        r.push(verdict(p.p, set.clevel(), 0.0, p.diff.mm, a, b));
+        g.row(&r);
+    }
+    doc.section("Pairwise Comparisons", g);
+
+    doc
+}
+
+// Lay out a batch as one summary table with a row per metric
+pub fn batch_document(b: &Batch) -> Document {
+    let set = &b.settings;
+    let compared = b.rows.iter().filter(|r| r.result.is_ok()).count();
+    let mut doc = Document {
+        title: format!("Bootstrap Batch Comparison of {} Metrics", b.rows.len()),
+        summary: apa_batch_summary(b),
+        sections: Vec::new(),
+    };
+    let f = |v: f64| science_pretty_format(v, 6);
+
+    let mut params = parameters(set, compared);
+    params.row(&["Metrics", &b.rows.len().to_string()]);
+    doc.section("Parameters", params);
+
+    let mut g = Grid::new(&[
+        "Metric",
+        "A",
+        "B",
+        "Count A",
+        "Count B",
+        "Mean A",
+        "Mean B",
+        "Diff",
+        "CI Low",
+        "CI High",
+        "p-Value",
+        "Adj p-Value",
+        "Result",
+    ]);
+    for r in &b.rows {
+        let name = |i: usize| r.names.get(i).cloned().unwrap_or_default();
+        let mut row = vec![r.metric.clone(), name(0), name(1)];
+
+        match &r.result {
+            Ok(c) => {
+                let s = &c.sdmean;
+                let (low, high) = bounds(set, s.amm > s.bmm);
+                row.extend([
+                    c.a.count.to_string(),
+                    c.b.count.to_string(),
+                    f(s.amm),
+                    f(s.bmm),
+                    f(s.dmm),
+                    if low { f(s.dml) } else { String::new() },
+                    if high { f(s.dmu) } else { String::new() },
+                    science_pretty_format(c.mean_p, 3),
+                    science_pretty_format(r.adj_p, 3),
+                    verdict(r.adj_p, set.clevel(), s.amm, s.bmm, &name(0), &name(1)),
+                ]);
+            }
+            Err(e) => {
+                row.resize(12, String::new());
+                row.push(e.clone());
+            }
+        }
+        g.row(&row);
+    }
+    doc.section("Summary", g);
+
+    if !b.rejected.is_empty() {
+        let mut g = Grid::new(&["Line", "Column", "Token"]);
+        for r in &b.rejected {
+            g.row(&[r.line.to_string(), r.column.to_string(), r.token.clone()]);
+        }
+        doc.section("Rejected Values", g);
+    }
+
+    doc
+}
+
+// The settings a report was calculated with
+fn parameters(s: &Settings, tests: usize) -> Grid {
+    let mut g = Grid::new(&["Setting", "Value"]);
+
+    g.row(&["Paired", if s.paired { "Yes" } else { "No" }]);
+    g.row(&["Tails", if s.two_tailed { "Two" } else { "One" }]);
+    g.row(&[
+        "Confidence Level",
+        &format!("{}%", science_pretty_format(s.confidence, 2)),
+    ]);
+    g.row(&["Iterations", &s.iterations.to_string()]);
+    g.row(&["Z Threshold", &science_pretty_format(s.zthresh, 2)]);
+    if let Some(seed) = s.seed {
+        g.row(&["Seed", &seed.to_string()]);
+    }
+    if s.paired {
+        g.row(&["Missing", s.missing.name()]);
+    }
+    if s.adjust != Adjust::None {
+        g.row(&["Adjust", s.adjust.name()]);
+        g.row(&["Tests", &tests.to_string()]);
+    }
+
+    g
+}
+
+// Which bounds of a difference to show, one tailed tests only have the side being tested
+pub fn bounds(s: &Settings, a_larger: bool) -> (bool, bool) {
+    if s.two_tailed {
+        (true, true)
+    } else {
+        (a_larger, !a_larger)
+    }
+}
+
+fn test_grid(s: &Settings) -> Grid {
+    let mut header = vec!["Test", "Statistic", "p-Value"];
+    if s.adjust != Adjust::None {
+        header.push("Adj p-Value");
+    }
+    header.push("Result");
+    Grid::new(&header)
+}
+
+fn test_row(
+    g: &mut Grid,
+    s: &Settings,
+    name: &str,
+    stat: String,
+    p: f64,
+    adj_p: f64,
+    result: String,
+) {
+    let mut r = vec![name.to_string(), stat, science_pretty_format(p, 3)];
+    if s.adjust != Adjust::None {
+        r.push(science_pretty_format(adj_p, 3));
+    }
+    r.push(result);
+    g.row(&r);
+}
+
+fn stat_grid(s: &Settings) -> Grid {
+    let mut header = vec!["Metric", "Value", "CI Low", "CI High", "p-Value"];
+    if s.adjust != Adjust::None {
+        header.push("Adj p-Value");
+    }
+    header.push("Result");
+    Grid::new(&header)
+}
+
+fn stat_row(g: &mut Grid, s: &Settings, name: &str, st: &Statresults, digits: usize) {
+    let mut r = stat_cells(s, name, st, digits);
+    r.push(significance(st.p, s.clevel()));
+    g.row(&r);
+}
+
+// The cells of a statistic row up to its result
+fn stat_cells(s: &Settings, name: &str, st: &Statresults, digits: usize) -> Vec<String> {
+    let mut r = vec![
+        name.to_string(),
+        science_pretty_format(st.value, digits),
+        science_pretty_format(st.l, digits),
+        science_pretty_format(st.u, digits),
+        science_pretty_format(st.p, 3),
+    ];
+    if s.adjust != Adjust::None {
+        r.push(science_pretty_format(st.adj_p, 3));
+    }
+    r
+}
+
+fn unique_grid(d: &Descriptive) -> Grid {
+    let mut g = Grid::new(&["Value", "Count"]);
+
+    for (v, n) in d.unique_values.iter().zip(&d.unique_counts) {
+        g.row(&[science_pretty_format(*v, 6), n.to_string()]);
+    }
+    g
+}
+
+// Null hypothesis outcome in the words of the text report
+fn verdict(p: f64, clevel: f64, a: f64, b: f64, an: &str, bn: &str) -> String {
+    if p > clevel {
+        format!("H0 = True, {} ≈ {}", an, bn)
+    } else if a > b {
+        format!("H0 = False, {} > {}", an, bn)
+    } else {
+        format!("H0 = False, {} < {}", an, bn)
+    }
+}
+
+fn significance(p: f64, clevel: f64) -> String {
+    if p <= clevel {
+        "Significant".to_string()
+    } else {
+        "Not Significant".to_string()
+    }
+}
+
+// Render a report as Markdown tables
+pub fn markdown(doc: &Document) -> String {
+    let mut out = format!("# {}\n", doc.title);
+    let cell = |s: &str| s.replace('|', "\\|");
+
+    if !doc.summary.is_empty() {
+        out.push_str(&format!("\n## Summary\n\n{}\n", doc.summary.join("  \n")));
+    }
+
+    for sec in &doc.sections {
+        out.push_str(&format!("\n## {}\n\n", sec.title));
+
+        let g = &sec.grid;
+        let header: Vec<String> = g.header.iter().map(|h| cell(h)).collect();
+        out.push_str(&format!("| {} |\n", header.join(" | ")));
+
+        // Numbers line up on the right, the name column on the left
+        let align: Vec<&str> = (0..g.header.len())
+            .map(|i| if i == 0 { ":---" } else { "---:" })
+            .collect();
+        out.push_str(&format!("| {} |\n", align.join(" | ")));
+
+        for r in &g.rows {
+            let r: Vec<String> = r.iter().map(|c| cell(c)).collect();
+            out.push_str(&format!("| {} |\n", r.join(" | ")));
+        }
+    }
+
+    out
+}
+
+// Render a report as booktabs tables to include in a paper, the summary as the note under the first
+pub fn latex(doc: &Document) -> String {
+    let mut out = String::new();
+
+    for (i, sec) in doc.sections.iter().enumerate() {
+        let g = &sec.grid;
+        let row = |cells: &[String]| {
+            let cells: Vec<String> = cells.iter().map(|c| latex_escape(c)).collect();
+            format!("{} \\\\\n", cells.join(" & "))
+        };
+
+        out.push_str("\\begin{table}[htbp]\n\\centering\n");
+        out.push_str(&format!("\\caption{{{}}}\n", latex_escape(&sec.title)));
+        out.push_str(&format!(
+            "\\begin{{tabular}}{{l{}}}\n\\toprule\n",
+            "r".repeat(g.header.len().saturating_sub(1))
+        ));
+        out.push_str(&row(&g.header));
+        out.push_str("\\midrule\n");
+        for r in &g.rows {
+            out.push_str(&row(r));
+        }
+        out.push_str("\\bottomrule\n\\end{tabular}\n");
+
+        if i == 0 && !doc.summary.is_empty() {
+            let notes: Vec<String> = doc.summary.iter().map(|l| latex_escape(l)).collect();
+            out.push_str(&format!(
+                "\\par\\smallskip\n\\parbox{{\\linewidth}}{{\\footnotesize \\textit{{Note.}} {}}}\n",
+                notes.join(" ")
+            ));
+        }
+        out.push_str("\\end{table}\n\n");
+    }
+    out
+}
+
+// Escape the characters LaTeX treats specially, the symbols the report uses become their LaTeX forms
+fn latex_escape(s: &str) -> String {
+    let mut out = String::new();
+
+    for ch in s.chars() {
+        match ch {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(ch);
+            }
+            '~' => out.push_str("\\textasciitilde{}"),
+            '^' => out.push_str("\\textasciicircum{}"),
+            '<' => out.push_str("$<$"),
+            '>' => out.push_str("$>$"),
+            'ρ' => out.push_str("$\\rho$"),
+            'τ' => out.push_str("$\\tau$"),
+            '²' => out.push_str("\\textsuperscript{2}"),
+            '≈' => out.push_str("$\\approx$"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// Render a report as tab separated tables for pasting into spreadsheets, a blank line between sections
+pub fn tsv(doc: &Document) -> String {
+    let mut out = String::new();
+
+    for sec in &doc.sections {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&sec.title);
+        out.push('\n');
+        out.push_str(&tsv_grid(&sec.grid));
+    }
+    out
+}
+
+// One table as tab separated lines, tabs and newlines inside cells become spaces
+pub fn tsv_grid(g: &Grid) -> String {
+    let mut out = String::new();
+    let clean = |c: &String| c.replace(['\t', '\n'], " ");
+
+    for r in std::iter::once(&g.header).chain(&g.rows) {
+        let cells: Vec<String> = r.iter().map(clean).collect();
+        out.push_str(&cells.join("\t"));
+        out.push('\n');
+    }
+    out
+}
+
+// Render a self contained HTML page, charts are embedded as inline SVG
+pub fn html(doc: &Document, charts: &[Chart]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        escape(&doc.title),
+        STYLE,
+        escape(&doc.title)
+    );
+
+    if !doc.summary.is_empty() {
+        out.push_str("<h2>Summary</h2>\n");
+        for line in &doc.summary {
+            out.push_str(&format!("<p>{}</p>\n", escape(line)));
+        }
+    }
+
+    for sec in &doc.sections {
+        out.push_str(&format!("<h2>{}</h2>\n<table>\n<tr>", escape(&sec.title)));
+        for h in &sec.grid.header {
+            out.push_str(&format!("<th>{}</th>", escape(h)));
+        }
+        out.push_str("</tr>\n");
+
+        for r in &sec.grid.rows {
+            out.push_str("<tr>");
+            for (i, c) in r.iter().enumerate() {
+                let tag = if i == 0 { "th" } else { "td" };
+                out.push_str(&format!("<{tag}>{}</{tag}>", escape(c)));
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n");
+    }
+
+    if !charts.is_empty() {
+        out.push_str("<h2>Plots</h2>\n");
+        for chart in charts {
+            out.push_str(&format!(
+                "<figure>\n{}</figure>\n",
+                svg(chart, EXPORT_W, EXPORT_H)
+            ));
+        }
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+const STYLE: &str = "body { font-family: Helvetica, Arial, sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.75em; }
+td { text-align: right; font-variant-numeric: tabular-nums; }
+th { text-align: left; background: #f4f4f4; }
+figure { margin: 1em 0; }
+svg { max-width: 100%; height: auto; }
+";
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Save a report, the file extension picks Markdown, HTML, PDF or LaTeX tables
+pub fn save_report(doc: &Document, charts: &[Chart], path: &Path) -> Result<(), String> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    let bytes = match ext.as_str() {
+        "md" | "markdown" => markdown(doc).into_bytes(),
+        "html" | "htm" => html(doc, charts).into_bytes(),
+        "pdf" => pdf(doc, charts),
+        "tex" => latex(doc).into_bytes(),
+        "tsv" => tsv(doc).into_bytes(),
+        _ => return Err("Report Format Error, use .md, .html, .pdf, .tex or .tsv".to_string()),
+    };
+
+    std::fs::write(path, bytes).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latex_from_sections() {
+        let mut doc = Document {
+            title: "T".to_string(),
+            summary: vec!["A: M = 1.00, 95% CI [0.50, 1.50].".to_string()],
+            sections: Vec::new(),
+        };
+        let mut g = Grid::new(&["Metric", "A_1", "B"]);
+        g.row(&["Spearman's ρ", "0.5", "R²"]);
+        doc.section("Tests & More", g);
+        doc.section("Empty", Grid::new(&["Setting"]));
+
+        let tex = latex(&doc);
+        assert_eq!(tex.matches("\\begin{table}").count(), 2);
+        assert!(tex.contains("\\caption{Tests \\& More}"));
+        assert!(tex.contains("\\begin{tabular}{lrr}"));
+        assert!(tex.contains("Metric & A\\_1 & B \\\\\n"));
+        assert!(tex.contains("Spearman's $\\rho$ & 0.5 & R\\textsuperscript{2} \\\\\n"));
+        assert!(tex.contains("95\\% CI"));
+        assert_eq!(tex.matches("Note.").count(), 1);
+    }
+}
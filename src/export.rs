@@ -0,0 +1,312 @@
+use crate::plot::*;
+
+use embedded_graphics::mono_font::{iso_8859_1::FONT_7X13, MonoTextStyle};
+use embedded_graphics::pixelcolor::Rgb888;
+use embedded_graphics::prelude::*;
+use embedded_graphics::text::{Baseline, Text};
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::path::Path;
+use tiny_skia::{
+    Paint, PathBuilder, Pixmap, PremultipliedColorU8, Rect, Stroke, StrokeDash, Transform,
+};
+
+// Default size of exported charts in pixels
+pub const EXPORT_W: f64 = 800.0;
+pub const EXPORT_H: f64 = 600.0;
+
+// Canvas writing SVG elements into a string
+pub struct Svgcanvas {
+    out: String,
+}
+
+// Canvas rasterizing onto a pixmap for PNG files
+pub struct Pngcanvas {
+    pixmap: Pixmap,
+}
+
+// Pixmap view embedded-graphics draws glyphs into, optionally turned a quarter left
+struct Glyphtarget<'a> {
+    pixmap: &'a mut Pixmap,
+    origin: (i32, i32),
+    vertical: bool,
+}
+
+// Render a chart as an SVG document
+pub fn svg(chart: &Chart, w: f64, h: f64) -> String {
+    let mut c = Svgcanvas { out: String::new() };
+
+    writeln!(
+        c.out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="Helvetica, Arial, sans-serif" font-size="12">"#
+    )
+    .unwrap();
+    draw(chart, &mut c, w, h);
+    c.out.push_str("</svg>\n");
+
+    c.out
+}
+
+// Render a chart as PNG file contents
+pub fn png(chart: &Chart, w: f64, h: f64) -> Result<Vec<u8>, String> {
+    let pixmap = Pixmap::new(w as u32, h as u32).ok_or("Image Size Error")?;
+    let mut c = Pngcanvas { pixmap };
+
+    draw(chart, &mut c, w, h);
+
+    c.pixmap.encode_png().map_err(|e| e.to_string())
+}
+
+// Save a chart, the file extension picks SVG or PNG
+pub fn save_chart(chart: &Chart, path: &Path, w: f64, h: f64) -> Result<(), String> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    let bytes = match ext.as_str() {
+        "svg" => svg(chart, w, h).into_bytes(),
+        "png" => png(chart, w, h)?,
+        _ => return Err("Export Format Error, use .svg or .png".to_string()),
+    };
+
+    std::fs::write(path, bytes).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// File name for a chart built from its title, e.g. "box_plot_tukey_fence"
+pub fn chart_file_name(chart: &Chart) -> String {
+    let mut out = String::new();
+
+    for ch in chart.title.to_lowercase().chars() {
+        if ch.is_ascii_alphanumeric() {
+            out.push(ch);
+        } else if !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+
+    out.trim_matches('_').to_string()
+}
+
+fn svg_color(c: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", c.0, c.1, c.2)
+}
+
+fn svg_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Canvas for Svgcanvas {
+    fn line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, color: Rgb, dashed: bool) {
+        writeln!(
+            self.out,
+            r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}"{}/>"#,
+            x1,
+            y1,
+            x2,
+            y2,
+            svg_color(color),
+            if dashed {
+                r#" stroke-dasharray="4 3""#
+            } else {
+                ""
+            }
+        )
+        .unwrap();
+    }
+
+    fn rect(&mut self, x: f64, y: f64, w: f64, h: f64, color: Rgb, fill: bool) {
+        let paint = if fill {
+            format!(r#"fill="{}""#, svg_color(color))
+        } else {
+            format!(r#"fill="none" stroke="{}""#, svg_color(color))
+        };
+        writeln!(
+            self.out,
+            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" {}/>"#,
+            x, y, w, h, paint
+        )
+        .unwrap();
+    }
+
+    fn circle(&mut self, x: f64, y: f64, r: f64, color: Rgb) {
+        writeln!(
+            self.out,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="none" stroke="{}"/>"#,
+            x,
+            y,
+            r,
+            svg_color(color)
+        )
+        .unwrap();
+    }
+
+    fn text(&mut self, x: f64, y: f64, s: &str, anchor: Anchor, color: Rgb) {
+        let anchor = match anchor {
+            Anchor::Start => "start",
+            Anchor::Middle => "middle",
+            Anchor::End => "end",
+        };
+        writeln!(
+            self.out,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="{}" fill="{}">{}</text>"#,
+            x,
+            y,
+            anchor,
+            svg_color(color),
+            svg_escape(s)
+        )
+        .unwrap();
+    }
+
+    fn vtext(&mut self, x: f64, y: f64, s: &str, color: Rgb) {
+        writeln!(
+            self.out,
+            r#"<text x="{x:.1}" y="{y:.1}" text-anchor="middle" fill="{}" transform="rotate(-90 {x:.1} {y:.1})">{}</text>"#,
+            svg_color(color),
+            svg_escape(s)
+        )
+        .unwrap();
+    }
+}
+
+impl Pngcanvas {
+    fn paint(color: Rgb) -> Paint<'static> {
+        let mut paint = Paint::default();
+        paint.set_color_rgba8(color.0, color.1, color.2, 255);
+        paint.anti_alias = true;
+        paint
+    }
+
+    fn glyphs(&mut self, x: f64, y: f64, s: &str, color: Rgb, vertical: bool) {
+        let style = MonoTextStyle::new(&FONT_7X13, Rgb888::new(color.0, color.1, color.2));
+        let mut target = Glyphtarget {
+            pixmap: &mut self.pixmap,
+            origin: (x as i32, y as i32),
+            vertical,
+        };
+        // Glyphs are laid out from the origin, then turned if vertical
+        Text::with_baseline(s, Point::zero(), style, Baseline::Alphabetic)
+            .draw(&mut target)
+            .unwrap();
+    }
+
+    fn text_width(s: &str) -> f64 {
+        (s.chars().count() as u32 * FONT_7X13.character_size.width) as f64
+    }
+}
+
+impl Canvas for Pngcanvas {
+    fn line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, color: Rgb, dashed: bool) {
+        let mut pb = PathBuilder::new();
+        pb.move_to(x1 as f32, y1 as f32);
+        pb.line_to(x2 as f32, y2 as f32);
+        let path = match pb.finish() {
+            Some(p) => p,
+            None => return,
+        };
+
+        let stroke = Stroke {
+            width: 1.0,
+            dash: if dashed {
+                StrokeDash::new(vec![4.0, 3.0], 0.0)
+            } else {
+                None
+            },
+            ..Stroke::default()
+        };
+        self.pixmap.stroke_path(
+            &path,
+            &Pngcanvas::paint(color),
+            &stroke,
+            Transform::identity(),
+            None,
+        );
+    }
+
+    fn rect(&mut self, x: f64, y: f64, w: f64, h: f64, color: Rgb, fill: bool) {
+        let r = match Rect::from_xywh(x as f32, y as f32, w as f32, h as f32) {
+            Some(r) => r,
+            None => return,
+        };
+
+        if fill {
+            self.pixmap
+                .fill_rect(r, &Pngcanvas::paint(color), Transform::identity(), None);
+        } else {
+            let path = PathBuilder::from_rect(r);
+            self.pixmap.stroke_path(
+                &path,
+                &Pngcanvas::paint(color),
+                &Stroke::default(),
+                Transform::identity(),
+                None,
+            );
+        }
+    }
+
+    fn circle(&mut self, x: f64, y: f64, r: f64, color: Rgb) {
+        if let Some(path) = PathBuilder::from_circle(x as f32, y as f32, r as f32) {
+            self.pixmap.stroke_path(
+                &path,
+                &Pngcanvas::paint(color),
+                &Stroke::default(),
+                Transform::identity(),
+                None,
+            );
+        }
+    }
+
+    fn text(&mut self, x: f64, y: f64, s: &str, anchor: Anchor, color: Rgb) {
+        let w = Pngcanvas::text_width(s);
+        let x = match anchor {
+            Anchor::Start => x,
+            Anchor::Middle => x - w / 2.0,
+            Anchor::End => x - w,
+        };
+        self.glyphs(x, y, s, color, false);
+    }
+
+    fn vtext(&mut self, x: f64, y: f64, s: &str, color: Rgb) {
+        let w = Pngcanvas::text_width(s);
+        self.glyphs(x, y + w / 2.0, s, color, true);
+    }
+}
+
+impl OriginDimensions for Glyphtarget<'_> {
+    fn size(&self) -> Size {
+        Size::new(self.pixmap.width(), self.pixmap.height())
+    }
+}
+
+impl DrawTarget for Glyphtarget<'_> {
+    type Color = Rgb888;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let (w, h) = (self.pixmap.width() as i32, self.pixmap.height() as i32);
+
+        for Pixel(p, color) in pixels {
+            let (x, y) = if self.vertical {
+                (self.origin.0 + p.y, self.origin.1 - p.x)
+            } else {
+                (self.origin.0 + p.x, self.origin.1 + p.y)
+            };
+            if x < 0 || y < 0 || x >= w || y >= h {
+                continue;
+            }
+
+            if let Some(c) = PremultipliedColorU8::from_rgba(color.r(), color.g(), color.b(), 255) {
+                self.pixmap.pixels_mut()[(y * w + x) as usize] = c;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,853 @@
+use crate::analysis::Sample;
+
+use calamine::{open_workbook_auto, Reader};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Clone, Debug)]
+// Define a struct for a token that is not a number, positions are 1 based and offsets in bytes
+pub struct Rejected {
+    pub token: String,
+    pub line: usize,
+    pub column: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+// Define a struct for how numbers are written, a newline delimiter means one value per line
+pub struct Numberformat {
+    pub auto: bool,
+    pub decimal: char,
+    pub thousands: Option<char>,
+    pub delimiter: char,
+}
+
+impl Default for Numberformat {
+    fn default() -> Self {
+        Numberformat {
+            auto: false,
+            decimal: '.',
+            thousands: None,
+            delimiter: ',',
+        }
+    }
+}
+
+impl Numberformat {
+    // Check the separators can be told apart
+    pub fn check(&self) -> Result<(), String> {
+        if self.decimal == self.delimiter || Some(self.decimal) == self.thousands {
+            return Err("Number Format Error, Separators Must Differ".to_string());
+        }
+        if Some(self.delimiter) == self.thousands {
+            return Err("Number Format Error, Separators Must Differ".to_string());
+        }
+        Ok(())
+    }
+
+    // The format to use for some text, detected when set to auto
+    pub fn resolve(&self, text: &str) -> Numberformat {
+        if self.auto {
+            detect_format(text)
+        } else {
+            *self
+        }
+    }
+
+    // One format for several inputs read together, detected across all of them when set to auto
+    pub fn resolve_all<S: AsRef<str>>(&self, texts: &[S]) -> Numberformat {
+        if self.auto {
+            let all: Vec<&str> = texts.iter().map(|t| t.as_ref()).collect();
+            Numberformat {
+                auto: false,
+                ..detect_format(&all.join("\n"))
+            }
+        } else {
+            *self
+        }
+    }
+
+    // Describe the separators for the report, e.g. decimal comma, thousands point, semicolon delimited
+    pub fn describe(&self) -> String {
+        let name = |c: char| match c {
+            '.' => "point",
+            ',' => "comma",
+            ';' => "semicolon",
+            '\t' => "tab",
+            '\n' => "newline",
+            '\'' => "apostrophe",
+            _ => "other",
+        };
+        format!(
+            "decimal {}, thousands {}, {} delimited",
+            name(self.decimal),
+            self.thousands.map_or("none", name),
+            name(self.delimiter)
+        )
+    }
+
+    // Convert one token with whitespace removed, None if it is not a number in this format
+    pub fn number(&self, token: &str) -> Option<f64> {
+        let mut t = token.to_string();
+
+        if let Some(sep) = self.thousands {
+            if t.contains(sep) && !grouped(&t, sep, self.decimal) {
+                return None;
+            }
+            t = t.replace(sep, "");
+        }
+        if self.decimal != '.' {
+            if t.contains('.') {
+                return None;
+            }
+            t = t.replace(self.decimal, ".");
+        }
+
+        t.parse::<f64>().ok()
+    }
+
+    // Write values one per line with this decimal mark so they read back in this format, NA for missing
+    pub fn write(&self, values: &[f64]) -> String {
+        values
+            .iter()
+            .map(|v| {
+                if v.is_nan() {
+                    "NA".to_string()
+                } else {
+                    v.to_string().replace('.', &self.decimal.to_string())
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+// Thousands groups must be 1 to 3 digits then runs of exactly 3, e.g. 1.234.567,8
+fn grouped(t: &str, sep: char, decimal: char) -> bool {
+    let (int, frac) = t.split_once(decimal).unwrap_or((t, ""));
+    if frac.contains(sep) {
+        return false;
+    }
+    let int = int.trim_start_matches(['-', '+']);
+    let mut groups = int.split(sep);
+
+    let first = groups.next().unwrap_or("");
+    (1..=3).contains(&first.len())
+        && first.chars().all(|c| c.is_ascii_digit())
+        && groups.all(|g| g.len() == 3 && g.chars().all(|c| c.is_ascii_digit()))
+}
+
+// Guess the number format from pasted text, falling back to plain comma separated values
+pub fn detect_format(text: &str) -> Numberformat {
+    let lines: Vec<String> = text
+        .lines()
+        .filter(|l| !l.trim_start().starts_with('#'))
+        .map(|l| {
+            l.split('#')
+                .next()
+                .unwrap_or("")
+                .chars()
+                .filter(|c| !c.is_whitespace() || *c == '\t')
+                .collect()
+        })
+        .filter(|l: &String| !l.is_empty())
+        .collect();
+    let has = |c: char| lines.iter().any(|l| l.contains(c));
+
+    // Tabs or semicolons between values leave the comma free to be a decimal mark
+    for delimiter in ['\t', ';'] {
+        if has(delimiter) {
+            let cells = lines.iter().flat_map(|l| l.split(delimiter));
+            return Numberformat {
+                auto: true,
+                delimiter,
+                ..cell_format(cells)
+            };
+        }
+    }
+
+    // One value per line with a comma inside, e.g. 1.234,56 or 12,5 or 1,234.5
+    if lines.len() >= 2 && has(',') {
+        let decimal_comma = Numberformat {
+            auto: true,
+            decimal: ',',
+            thousands: Some('.'),
+            delimiter: '\n',
+        };
+        let thousands_comma = Numberformat {
+            auto: true,
+            decimal: '.',
+            thousands: Some(','),
+            delimiter: '\n',
+        };
+        let all = |f: &Numberformat| lines.iter().all(|l| is_missing(l) || f.number(l).is_some());
+
+        // 1,234 on every line reads better as a thousands separator
+        let tails_of_three = lines
+            .iter()
+            .filter_map(|l| l.split_once(','))
+            .all(|(_, t)| t.len() == 3);
+
+        if all(&decimal_comma) && !tails_of_three {
+            return decimal_comma;
+        }
+        if all(&thousands_comma) {
+            return thousands_comma;
+        }
+    }
+
+    Numberformat {
+        auto: true,
+        ..Numberformat::default()
+    }
+}
+
+// Decimal comma when any cell has a comma, with points as thousands separators if there are any
+fn cell_format<'a, I: Iterator<Item = &'a str>>(cells: I) -> Numberformat {
+    let mut f = Numberformat::default();
+    let (mut comma, mut point) = (false, false);
+
+    for c in cells {
+        comma |= c.contains(',');
+        point |= c.contains('.');
+    }
+
+    if comma {
+        f.decimal = ',';
+        f.thousands = if point { Some('.') } else { None };
+    }
+    f
+}
+
+#[derive(Clone, Debug, Default)]
+// Define a struct for parsed input, the numbers plus anything that was rejected
+// Slots keep one entry per cell with NaN where a value is missing or rejected, for lining up paired rows
+pub struct Parsed {
+    pub values: Vec<f64>,
+    pub slots: Vec<f64>,
+    pub rejected: Vec<Rejected>,
+    pub suspicious: Vec<Rejected>,
+}
+
+// Convert CSV from the main windows to arrays of floats, also clean up stray whitespace
+pub fn csv_split(inp: &str) -> Vec<f64> {
+    csv_parse(inp, &Numberformat::default()).values
+}
+
+// Split on the delimiter and newlines like csv_split, keeping track of tokens that do not parse
+pub fn csv_parse(inp: &str, format: &Numberformat) -> Parsed {
+    let format = format.resolve(inp);
+    let mut out = Parsed::default();
+    let mut offset = 0;
+    // Slots from blank lines at the end of the text are not rows
+    let mut trailing = 0;
+    // In a column of single values a line like 12,5 is more likely a decimal comma than two values
+    let column = format.delimiter == ','
+        && format.decimal == '.'
+        && inp.lines().any(|l| {
+            let l = l.split('#').next().unwrap_or("").trim();
+            !l.is_empty() && !l.contains(',')
+        });
+
+    for (l, full) in inp.split('\n').enumerate() {
+        let mut start = offset;
+
+        // Comment lines are not rows, the rest of a line after # is ignored
+        if full.trim_start().starts_with('#') {
+            offset += full.len() + 1;
+            continue;
+        }
+        let line = full.split('#').next().unwrap_or("");
+
+        if line.trim().is_empty() {
+            out.slots.push(f64::NAN);
+            trailing += 1;
+            offset += full.len() + 1;
+            continue;
+        }
+        trailing = 0;
+
+        let fields: Vec<&str> = line.split(format.delimiter).collect();
+        if column && fields.len() == 2 && split_decimal(fields[0], fields[1]) {
+            let lead = line.len() - line.trim_start().len();
+            let token = line.trim();
+            out.suspicious.push(Rejected {
+                token: token.to_string(),
+                line: l + 1,
+                column: line[..lead].chars().count() + 1,
+                start: offset + lead,
+                end: offset + lead + token.len(),
+            });
+        }
+        for (i, field) in fields.iter().enumerate() {
+            let clean: String = field.chars().filter(|c| !c.is_whitespace()).collect();
+
+            if clean.is_empty() {
+                // A delimiter at the end of a line does not start another cell
+                if i + 1 < fields.len() {
+                    out.slots.push(f64::NAN);
+                }
+            } else if is_missing(&clean) {
+                out.slots.push(f64::NAN);
+            } else {
+                match format.number(&clean) {
+                    Some(v) => {
+                        out.values.push(v);
+                        out.slots.push(v);
+                    }
+                    None => {
+                        // Point at the token without its surrounding whitespace
+                        let lead = field.len() - field.trim_start().len();
+                        let token = field.trim();
+                        out.rejected.push(Rejected {
+                            token: token.to_string(),
+                            line: l + 1,
+                            column: line[..start - offset + lead].chars().count() + 1,
+                            start: start + lead,
+                            end: start + lead + token.len(),
+                        });
+                        out.slots.push(f64::NAN);
+                    }
+                }
+            }
+            start += field.len() + 1;
+        }
+        offset += full.len() + 1;
+    }
+
+    out.slots.truncate(out.slots.len() - trailing);
+    out
+}
+
+// Digits, a comma and one or two digits with no space around the comma, e.g. 12,5
+// Only checked in a column of single values, in a comma separated list 1,2 is just two numbers
+fn split_decimal(before: &str, after: &str) -> bool {
+    let int = before.trim_start().trim_start_matches(['-', '+']);
+    let frac = after.trim_end();
+
+    !int.is_empty()
+        && int.chars().all(|c| c.is_ascii_digit())
+        && (1..=2).contains(&frac.len())
+        && frac.chars().all(|c| c.is_ascii_digit())
+}
+
+// Split text on separator lines of three or more dashes, each block is one group
+pub fn split_blocks(text: &str) -> Vec<String> {
+    let mut out = vec![String::new()];
+
+    for line in text.split('\n') {
+        let t = line.trim();
+        if t.len() >= 3 && t.chars().all(|c| c == '-') {
+            out.push(String::new());
+        } else {
+            let block = out.last_mut().unwrap();
+            block.push_str(line);
+            block.push('\n');
+        }
+    }
+
+    out
+}
+
+// Check for separator lines that split one stream into groups
+pub fn has_blocks(text: &str) -> bool {
+    split_blocks(text).len() > 1
+}
+
+// Take a header line off the top of a single column, the line is commented out so positions hold
+pub fn take_header(text: &str) -> (String, String) {
+    let first = text
+        .split('\n')
+        .enumerate()
+        .find(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'));
+
+    let (n, line) = match first {
+        Some(f) => f,
+        None => return (String::new(), text.to_string()),
+    };
+
+    // A word that is neither a number nor a missing value marks a header
+    let name = line.split('#').next().unwrap_or("").trim();
+    let header = name.split([',', ';', '\t']).map(|t| t.trim()).any(|t| {
+        t.chars().any(|c| c.is_alphabetic()) && !is_missing(t) && t.parse::<f64>().is_err()
+    });
+    if !header {
+        return (String::new(), text.to_string());
+    }
+
+    let body: Vec<String> = text
+        .split('\n')
+        .enumerate()
+        .map(|(i, l)| {
+            if i == n {
+                format!("#{}", l)
+            } else {
+                l.to_string()
+            }
+        })
+        .collect();
+    (name.to_string(), body.join("\n"))
+}
+
+// Recognize the usual spellings of a missing value
+pub fn is_missing(token: &str) -> bool {
+    ["na", "n/a", "nan", "null"].contains(&token.to_lowercase().as_str())
+}
+
+#[derive(Clone, Debug, Default)]
+// Define a struct for a delimited text file, headers plus rows of cells
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub lines: Vec<usize>,
+    pub format: Numberformat,
+}
+
+impl Table {
+    // Numbers in one column by row, NaN marks a blank, missing or non numeric cell
+    pub fn column(&self, col: usize) -> Vec<f64> {
+        self.parse_column(col).slots
+    }
+
+    // Check whether any cell holds a number
+    pub fn has_numbers(&self) -> bool {
+        (0..self.headers.len()).any(|c| self.column(c).iter().any(|v| !v.is_nan()))
+    }
+
+    // Parse one column like csv_parse, rejected cells are placed by line and field number
+    pub fn parse_column(&self, col: usize) -> Parsed {
+        let mut out = Parsed::default();
+
+        for (i, r) in self.rows.iter().enumerate() {
+            let c = r.get(col).map_or("", |c| c.trim());
+            match self.format.number(&c.replace(' ', "")) {
+                Some(v) if !is_missing(c) => {
+                    out.values.push(v);
+                    out.slots.push(v);
+                }
+                _ => {
+                    if !c.is_empty() && !is_missing(c) {
+                        out.rejected.push(Rejected {
+                            token: c.to_string(),
+                            line: self.lines.get(i).copied().unwrap_or(i + 1),
+                            column: col + 1,
+                            start: 0,
+                            end: 0,
+                        });
+                    }
+                    out.slots.push(f64::NAN);
+                }
+            }
+        }
+
+        out
+    }
+
+    // Split a long format table into groups, in the order each group first appears
+    pub fn groups(&self, group_col: usize, value_col: usize) -> Vec<Sample> {
+        let mut out: Vec<Sample> = Vec::new();
+
+        for r in &self.rows {
+            let (g, v) = match (r.get(group_col), r.get(value_col)) {
+                (Some(g), Some(v)) => (g.trim(), v.trim()),
+                _ => continue,
+            };
+            let v = match self.format.number(&v.replace(' ', "")) {
+                Some(x) if !is_missing(v) => x,
+                _ => continue,
+            };
+
+            match out.iter_mut().find(|s| s.name == g) {
+                Some(s) => s.values.push(v),
+                None => out.push(Sample {
+                    name: g.to_string(),
+                    values: vec![v],
+                }),
+            }
+        }
+
+        out
+    }
+}
+
+// Read CSV or TSV text, the first line is taken as headers unless it is all numbers
+pub fn read_table(text: &str) -> Table {
+    let delim = delimiter(text);
+    let (lines, rows): (Vec<usize>, Vec<Vec<String>>) = text
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .enumerate()
+        // Headers may use # but the rest of a data line after # is a comment
+        .map(|(n, (i, l))| {
+            let l = if n > 0 {
+                l.split('#').next().unwrap_or("")
+            } else {
+                l
+            };
+            (i + 1, split_record(l, delim))
+        })
+        .unzip();
+
+    // A comma inside the cells of a file split on something else is a decimal mark
+    let format = if delim == ',' {
+        Numberformat::default()
+    } else {
+        cell_format(rows.iter().skip(1).flatten().map(|c| c.as_str()))
+    };
+
+    table_from_rows(lines, rows, format)
+}
+
+// Build a table from rows of cells and their line numbers, the first row is taken as headers unless it is all numbers
+fn table_from_rows(
+    mut lines: Vec<usize>,
+    mut rows: Vec<Vec<String>>,
+    format: Numberformat,
+) -> Table {
+    let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let has_headers = rows.first().is_some_and(|r| {
+        r.iter().any(|c| {
+            let c = c.trim();
+            !c.is_empty() && !is_missing(c) && format.number(&c.replace(' ', "")).is_none()
+        })
+    });
+
+    let mut headers: Vec<String> = if has_headers {
+        lines.remove(0);
+        rows.remove(0)
+            .iter()
+            .map(|c| c.trim().to_string())
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    // Name any missing or blank headers by position
+    headers.resize(width, String::new());
+    for (i, h) in headers.iter_mut().enumerate() {
+        if h.is_empty() {
+            *h = format!("Column {}", i + 1);
+        }
+    }
+
+    Table {
+        headers,
+        rows,
+        lines,
+        format,
+    }
+}
+
+// Guess the field delimiter from the first line, spaces only when there is nothing else
+fn delimiter(text: &str) -> char {
+    let first = text
+        .lines()
+        .find(|l| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .unwrap_or("")
+        .trim();
+
+    ['\t', ';', ',']
+        .into_iter()
+        .max_by_key(|d| first.matches(*d).count())
+        .filter(|d| first.contains(*d))
+        .unwrap_or(if first.contains(' ') { ' ' } else { ',' })
+}
+
+// Split one line into fields, double quotes may wrap delimiters and "" is a quote
+// Runs of spaces count as one delimiter when splitting on spaces
+fn split_record(line: &str, delim: char) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == delim && !quoted => out.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    out.push(field);
+
+    if delim == ' ' {
+        out.retain(|f| !f.is_empty());
+    }
+    out
+}
+
+#[derive(Clone, Debug, Default)]
+// Define a struct for one worksheet, cells as text with cell A1 at row 0 column 0
+pub struct Sheet {
+    pub name: String,
+    pub cells: Vec<Vec<String>>,
+}
+
+impl Sheet {
+    // Table from a range such as B2:D40, or the whole sheet when the range is blank
+    pub fn table(&self, range: &str) -> Result<Table, String> {
+        // The whole sheet starts at its first used column
+        let (r0, c0, r1, c1) = if range.trim().is_empty() {
+            let left = self
+                .cells
+                .iter()
+                .filter_map(|r| r.iter().position(|c| !c.trim().is_empty()))
+                .min()
+                .unwrap_or(0);
+            (0, left, usize::MAX, usize::MAX)
+        } else {
+            parse_range(range)?
+        };
+
+        let (lines, rows): (Vec<usize>, Vec<Vec<String>>) = self
+            .cells
+            .iter()
+            .enumerate()
+            .skip(r0)
+            .take(r1.saturating_sub(r0).saturating_add(1))
+            .map(|(i, r)| {
+                let row: Vec<String> = r
+                    .iter()
+                    .skip(c0)
+                    .take(c1.saturating_sub(c0).saturating_add(1))
+                    .cloned()
+                    .collect();
+                (i + 1, row)
+            })
+            .filter(|(_, r)| r.iter().any(|c| !c.trim().is_empty()))
+            .unzip();
+
+        if rows.is_empty() {
+            return Err(format!("No Data Found In {}", self.name));
+        }
+        Ok(table_from_rows(lines, rows, Numberformat::default()))
+    }
+}
+
+// Read every sheet of an xlsx, xls or ods workbook
+pub fn read_workbook(path: &Path) -> Result<Vec<Sheet>, String> {
+    let mut book = open_workbook_auto(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut out = Vec::new();
+
+    for name in book.sheet_names() {
+        let range = book
+            .worksheet_range(&name)
+            .map_err(|e| format!("{}: {}", name, e))?;
+
+        // Pad out to A1 so ranges can be given in the usual spreadsheet terms
+        let (top, left) = range.start().unwrap_or((0, 0));
+        let mut cells = vec![Vec::new(); top as usize];
+        for r in range.rows() {
+            let mut row = vec![String::new(); left as usize];
+            row.extend(r.iter().map(|c| c.to_string()));
+            cells.push(row);
+        }
+
+        out.push(Sheet { name, cells });
+    }
+
+    Ok(out)
+}
+
+// Check the file extension for a spreadsheet workbook
+pub fn is_workbook(path: &Path) -> bool {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    ["xlsx", "xlsm", "xls", "xlsb", "ods"].contains(&ext.as_str())
+}
+
+// Parse a range like B2:D40 or B:D into 0 based inclusive row and column bounds
+pub fn parse_range(range: &str) -> Result<(usize, usize, usize, usize), String> {
+    let err = || format!("Range Error, use a form like B2:D40 not {}", range.trim());
+    let (a, b) = range.trim().split_once(':').ok_or_else(err)?;
+    let (ar, ac) = cell_ref(a).ok_or_else(err)?;
+    let (br, bc) = cell_ref(b).ok_or_else(err)?;
+
+    Ok((
+        ar.unwrap_or(0).min(br.unwrap_or(usize::MAX)),
+        ac.min(bc),
+        ar.unwrap_or(0).max(br.unwrap_or(usize::MAX)),
+        ac.max(bc),
+    ))
+}
+
+// Split a reference like AB12 into a 0 based row, if given, and column
+fn cell_ref(s: &str) -> Option<(Option<usize>, usize)> {
+    let s = s.trim().to_uppercase();
+    let letters: String = s.chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+    let digits = &s[letters.len()..];
+
+    if letters.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let col = letters
+        .chars()
+        .fold(0, |n, c| n * 26 + (c as usize - 'A' as usize + 1))
+        - 1;
+    let row = match digits {
+        "" => None,
+        d => Some(d.parse::<usize>().ok()?.checked_sub(1)?),
+    };
+
+    Some((row, col))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // NaN never equals itself, so slots are compared with missing values as None
+    fn slots(p: &Parsed) -> Vec<Option<f64>> {
+        p.slots
+            .iter()
+            .map(|v| if v.is_nan() { None } else { Some(*v) })
+            .collect()
+    }
+
+    #[test]
+    fn csv_parse_slots_and_rejected() {
+        let text = "1\n2.5, x\n\nNA,3 # c\n12,5\n";
+        let p = csv_parse(text, &Numberformat::default());
+
+        assert_eq!(p.values, vec![1.0, 2.5, 3.0, 12.0, 5.0]);
+        assert_eq!(
+            slots(&p),
+            vec![
+                Some(1.0),
+                Some(2.5),
+                None,
+                None,
+                None,
+                Some(3.0),
+                Some(12.0),
+                Some(5.0)
+            ]
+        );
+
+        assert_eq!(p.rejected.len(), 1);
+        let r = &p.rejected[0];
+        assert_eq!((r.token.as_str(), r.line, r.column), ("x", 2, 6));
+        assert_eq!(&text[r.start..r.end], "x");
+
+        assert_eq!(p.suspicious.len(), 1);
+        let s = &p.suspicious[0];
+        assert_eq!((s.line, s.column), (5, 1));
+        assert_eq!(&text[s.start..s.end], "12,5");
+    }
+
+    #[test]
+    fn comma_lists_are_not_suspicious() {
+        let p = csv_parse("1,2\n3,4\n5,6", &Numberformat::default());
+
+        assert_eq!(p.values, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert!(p.suspicious.is_empty());
+        assert!(csv_parse("7\n12, 5\n1,234\n", &Numberformat::default())
+            .suspicious
+            .is_empty());
+    }
+
+    #[test]
+    fn detect_format_cases() {
+        let f = detect_format("1.234,56\n7,5\n");
+        assert_eq!(
+            (f.decimal, f.thousands, f.delimiter),
+            (',', Some('.'), '\n')
+        );
+
+        let f = detect_format("1,234\n5,678\n");
+        assert_eq!(
+            (f.decimal, f.thousands, f.delimiter),
+            ('.', Some(','), '\n')
+        );
+
+        let f = detect_format("1;2,5\n3;4\n");
+        assert_eq!((f.decimal, f.thousands, f.delimiter), (',', None, ';'));
+
+        let f = detect_format("1,2,3\n4.5, 6\n");
+        assert_eq!(
+            f,
+            Numberformat {
+                auto: true,
+                ..Numberformat::default()
+            }
+        );
+    }
+
+    #[test]
+    fn resolve_all_detects_once() {
+        let auto = Numberformat {
+            auto: true,
+            ..Numberformat::default()
+        };
+        // Alone the first column reads as thousands, together with 7,5 both read as decimal commas
+        let f = auto.resolve_all(&["1,234\n5,678", "7,5\n2,25"]);
+        assert_eq!((f.auto, f.decimal, f.delimiter), (false, ',', '\n'));
+        assert_eq!(csv_parse("1,234\n5,678", &f).values, vec![1.234, 5.678]);
+
+        let plain = Numberformat::default();
+        assert_eq!(plain.resolve_all(&["1,2\n3,4"]), plain);
+        assert_eq!(csv_parse("1,2\n3,4\n5,6", &plain).values.len(), 6);
+    }
+
+    #[test]
+    fn write_reads_back_in_decimal_comma() {
+        let f = Numberformat {
+            decimal: ',',
+            delimiter: ';',
+            ..Numberformat::default()
+        };
+        let values = vec![1.5, -2.25, 3.0, f64::NAN, 1234.5];
+        let text = f.write(&values);
+        assert_eq!(text, "1,5\n-2,25\n3\nNA\n1234,5");
+
+        let p = csv_parse(&text, &f);
+        assert!(p.rejected.is_empty());
+        assert_eq!(p.values, vec![1.5, -2.25, 3.0, 1234.5]);
+        assert_eq!(p.slots.len(), values.len());
+    }
+
+    #[test]
+    fn split_blocks_and_headers() {
+        let blocks = split_blocks("Control\n1\n2\n ---- \n# run 2\nTreated\n3\n--\n4\n---\n");
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[2], "\n");
+        assert!(blocks[1].contains("--\n4"));
+
+        let (header, body) = take_header(&blocks[1]);
+        assert_eq!(header, "Treated");
+        assert_eq!(body, "# run 2\n#Treated\n3\n--\n4\n");
+        assert_eq!(csv_split(&body), vec![3.0, 4.0]);
+
+        // Numbers, missing values and exponents are data, not headers
+        for text in ["1,2\n", "NA\n3\n", "1e3\n"] {
+            assert_eq!(take_header(text), (String::new(), text.to_string()));
+        }
+        assert!(!has_blocks("1\n-5\n"));
+    }
+
+    #[test]
+    fn ranges_and_cell_refs() {
+        assert_eq!(cell_ref("a1"), Some((Some(0), 0)));
+        assert_eq!(cell_ref(" AB12 "), Some((Some(11), 27)));
+        assert_eq!(cell_ref("C"), Some((None, 2)));
+        assert_eq!(cell_ref("A0"), None);
+        assert_eq!(cell_ref("12"), None);
+        assert_eq!(cell_ref("B2x"), None);
+
+        assert_eq!(parse_range("B2:D40"), Ok((1, 1, 39, 3)));
+        // Corners in any order, whole columns run to the end of the sheet
+        assert_eq!(parse_range("D40:B2"), Ok((1, 1, 39, 3)));
+        assert_eq!(parse_range("B:C"), Ok((0, 1, usize::MAX, 2)));
+        assert!(parse_range("B2").is_err());
+        assert!(parse_range("B2:4").is_err());
+    }
+}
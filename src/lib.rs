@@ -0,0 +1,17 @@
+#![allow(clippy::many_single_char_names)]
+#![allow(clippy::manual_range_contains)]
+pub mod analysis;
+pub mod apa;
+pub mod api;
+pub mod batch;
+pub mod document;
+pub mod export;
+pub mod input;
+pub mod pdf;
+pub mod plot;
+pub mod prefs;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod report;
+pub mod session;
+pub mod stats;
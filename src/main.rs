@@ -1,18 +1,38 @@
 #![windows_subsystem = "windows"]
-#![allow(clippy::many_single_char_names)]
-#![allow(clippy::manual_range_contains)]
+use bootstrapcicalculator::analysis::*;
+use bootstrapcicalculator::batch::batch;
+use bootstrapcicalculator::document::*;
+use bootstrapcicalculator::export::*;
+use bootstrapcicalculator::input::*;
+use bootstrapcicalculator::plot::*;
+use bootstrapcicalculator::prefs::*;
+use bootstrapcicalculator::report::*;
+use bootstrapcicalculator::session::*;
+use bootstrapcicalculator::stats::Adjust;
+use fltk::draw::LineStyle;
 use fltk::{
-    app::*, button::*, dialog::*, frame::*, group::*, input::*, prelude::*, text::*, window::*,
+    app::*,
+    browser::Browser,
+    button::*,
+    dialog::*,
+    enums::{Align, CallbackTrigger, Color, Shortcut},
+    frame::*,
+    group::*,
+    input::*,
+    menu::{Choice, MenuBar, MenuFlag},
+    prelude::*,
+    text::*,
+    window::*,
 };
-use rand::Rng;
-use std::cmp::Ordering;
-use std::f64;
+
+use std::cell::RefCell;
+use std::rc::Rc;
 
 #[derive(Clone, Debug)]
 // Define a struct for the form fields
 struct Parameters {
-    data_a: TextEditor,
-    data_b: TextEditor,
+    columns: Scroll,
+    data: Pack,
     output: TextDisplay,
     paired_data: CheckButton,
     one_tailed: RadioRoundButton,
@@ -20,117 +40,350 @@ struct Parameters {
     cinterval: FloatInput,
     zthresh: FloatInput,
     iterations: IntInput,
+    adjust: Choice,
+    missing: Choice,
+    strict: CheckButton,
+    numbers: Rc<RefCell<Numberformat>>,
+    charts: Rc<RefCell<Vec<Chart>>>,
+    document: Rc<RefCell<Document>>,
+    seed: Rc<RefCell<String>>,
+    last: Rc<RefCell<Settings>>,
+    preset: Choice,
+    plot_choice: Choice,
+    plot_area: Frame,
 }
 
-#[derive(Clone, Debug)]
-// Define a struct for our dmeans and dsds
-struct Sdmeanresults {
-    amu: f64,
-    aml: f64,
-    amm: f64,
-    asu: f64,
-    asl: f64,
-    asm: f64,
-    bmu: f64,
-    bml: f64,
-    bmm: f64,
-    bsu: f64,
-    bsl: f64,
-    bsm: f64,
-    dmu: f64,
-    dml: f64,
-    dmm: f64,
-    dsu: f64,
-    dsl: f64,
-    dsm: f64,
-}
-
-#[derive(Clone, Debug)]
-// Define a struct for our Z Score Counts
-struct Zscoreresults {
-    pluscount: usize,
-    minuscount: usize,
-    pluspercent: f64,
-    minuspercent: f64,
-}
-
-#[derive(Clone, Debug)]
-// Define a struct for CI results
-struct CIresults {
-    mu: f64,
-    ml: f64,
-    mm: f64,
-    su: f64,
-    sl: f64,
-    sm: f64,
+// Canvas drawing charts with FLTK, offset to the widget position
+struct Fltkcanvas {
+    x: f64,
+    y: f64,
 }
 
 fn main() {
     let app = App::default();
+    let prefs = load_prefs();
+
+    // Scrolling strip of data columns, one text editor per group
+    let mut columns = Scroll::new(16, 35, 435, 439, "");
+    columns.set_type(ScrollType::Horizontal);
+    columns.set_scrollbar_size(15);
+    let mut data = Pack::new(16, 55, 435, 404, "");
+    data.set_type(PackType::Horizontal);
+    data.set_spacing(27);
+    data.end();
+    columns.end();
+
+    // Plot window, shown from the Plots button
+    let mut plot_wind = Window::new(150, 150, 640, 480, "Plots");
+    let plot_choice = Choice::new(60, 8, 300, 24, "Chart");
+    let mut save_button = Button::new(370, 8, 80, 24, "Save...");
+    let plot_area = Frame::new(0, 40, 640, 440, "");
+    plot_wind.resizable(&plot_area);
+    plot_wind.end();
 
     // Main Window
-    let mut wind = Window::new(100, 100, 737, 530, "Bootstrap Statistics Calculator v3.55");
+    let mut wind = Window::new(
+        100,
+        100,
+        prefs.width.max(WINDOW_W),
+        prefs.height.max(WINDOW_H),
+        "Bootstrap Statistics Calculator v3.55",
+    );
 
     // Fill the form structure
     let mut parameters = Parameters {
-        data_a: TextEditor::new(16, 30, 204, 404, ""),
-        data_b: TextEditor::new(247, 30, 204, 404, ""),
-        paired_data: CheckButton::new(556, 26, 105, 21, "Paired or Corr Data"),
-        one_tailed: RadioRoundButton::new(558, 54, 99, 21, "One Tailed"),
-        two_tailed: RadioRoundButton::new(558, 81, 99, 21, "Two Tailed"),
-        cinterval: FloatInput::new(558, 114, 54, 22, "CL"),
-        iterations: IntInput::new(558, 143, 54, 22, "Iterations"),
-        zthresh: FloatInput::new(558, 172, 54, 22, "Z Thresh"),
-        output: TextDisplay::new(480, 200, 230, 300, ""),
+        columns,
+        data,
+        paired_data: CheckButton::new(556, 51, 105, 21, "Paired or Corr Data"),
+        one_tailed: RadioRoundButton::new(558, 79, 99, 21, "One Tailed"),
+        two_tailed: RadioRoundButton::new(558, 106, 99, 21, "Two Tailed"),
+        cinterval: FloatInput::new(558, 139, 54, 22, "CL"),
+        iterations: IntInput::new(558, 168, 54, 22, "Iterations"),
+        zthresh: FloatInput::new(558, 197, 54, 22, "Z Thresh"),
+        adjust: Choice::new(558, 226, 130, 22, "Adjust"),
+        missing: Choice::new(558, 255, 130, 22, "Missing"),
+        preset: Choice::new(558, 284, 130, 22, "Preset"),
+        output: TextDisplay::new(480, 313, 230, 212, ""),
+        strict: CheckButton::new(556, 529, 105, 21, "Strict Input"),
+        numbers: Rc::new(RefCell::new(prefs.numbers)),
+        charts: Rc::new(RefCell::new(Vec::new())),
+        document: Rc::new(RefCell::new(Document::default())),
+        seed: Rc::new(RefCell::new(String::new())),
+        last: Rc::new(RefCell::new(Settings::default())),
+        plot_choice,
+        plot_area,
     };
 
-    // Text buffers for our inputs and output
-    let buf_a = TextBuffer::default();
-    let buf_b = TextBuffer::default();
+    // File menu
+    let mut menu = MenuBar::new(0, 0, 737, 25, "");
+    let mut p8 = parameters.clone();
+    menu.add(
+        "&File/&Open...",
+        Shortcut::Ctrl | 'o',
+        MenuFlag::Normal,
+        move |_| open_file(&mut p8),
+    );
+    let p15 = parameters.clone();
+    menu.add(
+        "&File/Open &Session...",
+        Shortcut::Ctrl | Shortcut::Shift | 'o',
+        MenuFlag::Normal,
+        move |m| {
+            let mut dialog = NativeFileChooser::new(NativeFileChooserType::BrowseFile);
+            dialog.set_filter("Sessions\t*.{toml,json}");
+            dialog.show();
+
+            let path = dialog.filename();
+            if !path.as_os_str().is_empty() {
+                open_session(&p15, &path, m);
+            }
+        },
+    );
+    menu.add(
+        "&File/Open &Recent",
+        Shortcut::None,
+        MenuFlag::Submenu,
+        |_| {},
+    );
+    let p16 = parameters.clone();
+    menu.add(
+        "&File/Sa&ve Session...",
+        Shortcut::Ctrl | 's',
+        MenuFlag::MenuDivider,
+        move |m| save_session_dialog(&p16, m),
+    );
+    let p25 = parameters.clone();
+    menu.add(
+        "&File/&Batch...",
+        Shortcut::Ctrl | 'b',
+        MenuFlag::MenuDivider,
+        move |_| batch_file(&p25),
+    );
+    let p10 = parameters.clone();
+    menu.add(
+        "&File/&Export Report...",
+        Shortcut::Ctrl | 'e',
+        MenuFlag::Normal,
+        move |_| {
+            export_report(
+                &p10,
+                &[
+                    ("HTML", "html"),
+                    ("Markdown", "md"),
+                    ("LaTeX Tables", "tex"),
+                    ("PDF", "pdf"),
+                    ("Tab Separated", "tsv"),
+                ],
+            )
+        },
+    );
+    let p12 = parameters.clone();
+    menu.add(
+        "&File/Export &PDF...",
+        Shortcut::Ctrl | 'p',
+        MenuFlag::Normal,
+        move |_| export_report(&p12, &[("PDF", "pdf")]),
+    );
+    let p11 = parameters.clone();
+    menu.add(
+        "&Edit/Copy &APA Summary",
+        Shortcut::Ctrl | Shortcut::Shift | 'c',
+        MenuFlag::Normal,
+        move |_| {
+            let summary = p11.document.borrow().summary.join("\n");
+            if summary.is_empty() {
+                alert(368, 265, "Calculate before copying a summary");
+            } else {
+                copy(&summary);
+            }
+        },
+    );
+    let p13 = parameters.clone();
+    menu.add(
+        "&Edit/Copy as &Table",
+        Shortcut::Ctrl | Shortcut::Shift | 't',
+        MenuFlag::Normal,
+        move |_| {
+            let document = p13.document.borrow();
+            if document.sections.is_empty() {
+                alert(368, 265, "Calculate before copying a table");
+            } else {
+                copy(&tsv(&document));
+            }
+        },
+    );
+    let p14 = parameters.clone();
+    menu.add(
+        "&Edit/Copy &Selected Section",
+        Shortcut::Ctrl | Shortcut::Shift | 's',
+        MenuFlag::Normal,
+        move |_| copy_section(&p14),
+    );
+    let p9 = parameters.clone();
+    menu.add(
+        "&Edit/&Number Format...",
+        Shortcut::None,
+        MenuFlag::Normal,
+        move |_| number_format_dialog(&p9),
+    );
+    let p17 = parameters.clone();
+    menu.add(
+        "&Edit/Random S&eed...",
+        Shortcut::None,
+        MenuFlag::Normal,
+        move |_| {
+            // Without a fixed seed the dialog offers the one behind the shown results, OK repeats them
+            let mut current = p17.seed.borrow().clone();
+            if current.is_empty() {
+                current = p17
+                    .last
+                    .borrow()
+                    .seed
+                    .map(|v| v.to_string())
+                    .unwrap_or_default();
+            }
+            if let Some(seed) = input_default(
+                "Seed for the resampling, blank draws a new one each time",
+                &current,
+            ) {
+                *p17.seed.borrow_mut() = seed.trim().to_string();
+            }
+        },
+    );
+    let p18 = parameters.clone();
+    menu.add(
+        "&Edit/&Reset to Defaults",
+        Shortcut::None,
+        MenuFlag::Normal,
+        move |m| {
+            let prefs = Prefs::default();
+            set_form(&p18, &prefs.settings, prefs.strict, prefs.numbers);
+            if let Some(mut w) = m.window() {
+                w.set_size(prefs.width, prefs.height);
+            }
+        },
+    );
+    let p20 = parameters.clone();
+    menu.add(
+        "&Presets/&Save Current As...",
+        Shortcut::None,
+        MenuFlag::Normal,
+        move |_| save_preset_dialog(&p20),
+    );
+    let p21 = parameters.clone();
+    menu.add(
+        "&Presets/&Import...",
+        Shortcut::None,
+        MenuFlag::Normal,
+        move |_| import_preset(&p21),
+    );
+    let p22 = parameters.clone();
+    menu.add(
+        "&Presets/&Export Selected...",
+        Shortcut::None,
+        MenuFlag::Normal,
+        move |_| export_preset(&p22),
+    );
+    let p23 = parameters.clone();
+    menu.add(
+        "&Presets/&Delete Selected",
+        Shortcut::None,
+        MenuFlag::Normal,
+        move |_| {
+            let Some(preset) = selected_preset(&p23) else {
+                alert(368, 265, "Pick a preset to delete");
+                return;
+            };
+            let question = format!("Delete the preset {}?", preset.name);
+            if choice2(368, 265, &question, "Cancel", "Delete", "") == Some(1) {
+                delete_preset(&preset.name);
+                preset_choices(&p23, "");
+            }
+        },
+    );
+    recent_menu(&mut menu, &parameters);
+
+    // Start with the A and B data columns
+    add_group(&mut parameters);
+    add_group(&mut parameters);
+
+    // Text buffer for our output
     let buf_out = TextBuffer::default();
 
-    // Data Labels for Main Input windows
-    Frame::new(16, 10, 51, 17, "Data A");
-    Frame::new(255, 10, 51, 17, "Data B");
-    Frame::new(610, 148, 20, 22, "K");
-    Frame::new(610, 119, 20, 22, "%");
-
-    // Format and initialize our main input windows
-    parameters.data_a.set_scrollbar_size(15);
-    parameters.data_b.set_scrollbar_size(15);
-    parameters.data_a.set_cursor_style(Cursor::Simple);
-    parameters.data_b.set_cursor_style(Cursor::Simple);
-    parameters.data_a.set_buffer(Some(buf_a));
-    parameters.data_b.set_buffer(Some(buf_b));
-    parameters.data_a.set_tab_nav(true);
-    parameters.data_b.set_tab_nav(true);
+    // Labels for the parameter units
+    Frame::new(610, 173, 20, 22, "K");
+    Frame::new(610, 144, 20, 22, "%");
 
     // Set output buffer
     parameters.output.set_buffer(Some(buf_out));
 
     // Group for radio buttons
-    let mut group_tailed = Group::new(555, 50, 100, 50, "");
+    let mut group_tailed = Group::new(555, 75, 100, 50, "");
     group_tailed.add(&parameters.one_tailed);
     group_tailed.add(&parameters.two_tailed);
     group_tailed.end();
 
-    // Set intial values for the form
-    parameters.two_tailed.toggle(true);
-    parameters.cinterval.set_value("95");
-    parameters.iterations.set_value("10");
-    parameters.zthresh.set_value("3.0");
+    // Set intial values for the form from the saved defaults
+    parameters
+        .adjust
+        .add_choice("None|Bonferroni|Holm|Benjamini-Hochberg");
+    parameters.missing.add_choice("Listwise|Pairwise");
+    set_form(&parameters, &prefs.settings, prefs.strict, prefs.numbers);
+
+    // Picking a preset fills in the form
+    preset_choices(&parameters, "");
+    let p24 = parameters.clone();
+    parameters.preset.set_callback(move |_| {
+        if let Some(preset) = selected_preset(&p24) {
+            set_form(&p24, &preset.settings, preset.strict, preset.numbers);
+        }
+    });
+
+    // Draw the selected chart
+    let p5 = parameters.clone();
+    parameters.plot_area.draw(move |w| draw_chart(&p5, w));
 
-    // Clone the parameters to use for the clear function
+    let mut p6 = parameters.clone();
+    parameters
+        .plot_choice
+        .set_callback(move |_| p6.plot_area.redraw());
+
+    let p7 = parameters.clone();
+    save_button.set_callback(move |_| save_plot(&p7));
+
+    // Clone the parameters to use for the other buttons
     let mut p2 = parameters.clone();
+    let mut p3 = parameters.clone();
+    let mut p4 = parameters.clone();
+    let p19 = parameters.clone();
+
+    // Add and remove data column buttons
+    let mut add_button = Button::new(16, 475, 100, 27, "Add Group");
+    add_button.set_callback(move |_| add_group(&mut p3));
+
+    let mut remove_button = Button::new(16, 505, 100, 27, "Remove Group");
+    remove_button.set_callback(move |_| remove_group(&mut p4));
 
     // Calculate button
-    let mut calculate_button = Button::new(130, 450, 200, 57, "Calculate");
+    let mut calculate_button = Button::new(130, 475, 200, 57, "Calculate");
     calculate_button.set_callback(move |_| calculate(&mut parameters));
 
     // clear button
-    let mut clear_button = Button::new(350, 450, 100, 57, "Clear");
+    let mut clear_button = Button::new(350, 475, 100, 27, "Clear");
     clear_button.set_callback(move |_| clear(&mut p2));
 
+    // plots button
+    let mut plots_button = Button::new(350, 505, 100, 27, "Plots");
+    plots_button.set_callback(move |_| plot_wind.show());
+
+    // The data columns take up any extra room, closing keeps the form as the next defaults
+    wind.resizable(&p19.columns);
+    wind.size_range(WINDOW_W, WINDOW_H, 0, 0);
+    wind.set_callback(move |w| {
+        save_defaults(&p19, w);
+        w.hide();
+    });
+
     // Show the window
     wind.end();
     wind.show();
@@ -139,1247 +392,1177 @@ fn main() {
     app.run().unwrap();
 }
 
-fn clear(p: &mut Parameters) {
-    p.output.buffer().unwrap().set_text("");
-    p.data_a.buffer().unwrap().set_text("");
-    p.data_b.buffer().unwrap().set_text("");
+// Get the text editors for every data column
+fn editors(p: &Parameters) -> Vec<TextEditor> {
+    (0..p.data.children())
+        .filter_map(|i| p.data.child(i))
+        .filter_map(|w| TextEditor::from_dyn_widget(&w))
+        .collect()
 }
 
-// Handle Calculate button
-fn calculate(p: &mut Parameters) {
-    // Output String
-    let mut out: String = String::from("");
+// Add a data column to the right of the existing ones
+fn add_group(p: &mut Parameters) {
+    let name = group_name(editors(p).len());
+
+    p.data.begin();
+    let mut editor = TextEditor::default()
+        .with_size(204, 404)
+        .with_label(&format!("Data {}", name))
+        .with_align(Align::Top | Align::Left);
+    p.data.end();
+
+    // Format and initialize the input window
+    editor.set_scrollbar_size(15);
+    editor.set_cursor_style(Cursor::Simple);
+    editor.set_buffer(Some(TextBuffer::default()));
+    editor.set_tab_nav(true);
+
+    // Style buffer marking rejected tokens, 'A' is plain, 'B' is rejected and 'C' is read but suspicious
+    let mut style = TextBuffer::default();
+    editor.set_highlight_data_ext(
+        style.clone(),
+        vec![
+            StyleTableEntryExt {
+                color: editor.text_color(),
+                font: editor.text_font(),
+                size: editor.text_size(),
+                ..Default::default()
+            },
+            StyleTableEntryExt {
+                color: Color::Red,
+                font: editor.text_font(),
+                size: editor.text_size(),
+                attr: TextAttr::Underline,
+                bgcolor: Color::from_rgb(255, 225, 225),
+            },
+            StyleTableEntryExt {
+                color: Color::from_rgb(160, 90, 0),
+                font: editor.text_font(),
+                size: editor.text_size(),
+                attr: TextAttr::Underline,
+                bgcolor: Color::from_rgb(255, 240, 200),
+            },
+        ],
+    );
+
+    // Keep the styles lined up with the text, edited text loses its mark
+    editor
+        .buffer()
+        .unwrap()
+        .add_modify_callback(move |pos, inserted, deleted, _, _| {
+            if deleted > 0 {
+                style.remove(pos, pos + deleted);
+            }
+            if inserted > 0 {
+                style.insert(pos, &"A".repeat(inserted as usize));
+            }
+        });
 
-    // Get the CSV data out of the two data fields
-    let mut a_v: Vec<f64> = csv_split(&p.data_a.buffer().unwrap().text());
-    let mut b_v: Vec<f64> = csv_split(&p.data_b.buffer().unwrap().text());
+    p.columns.redraw();
+}
 
-    if a_v.is_empty() {
-        p.data_a.buffer().unwrap().set_text("0.0");
-        a_v.push(0.0);
-    }
+// Remove the last data column, always keeping A and B
+fn remove_group(p: &mut Parameters) {
+    let mut e = editors(p);
 
-    if b_v.is_empty() {
-        p.data_b.buffer().unwrap().set_text("0.0");
-        b_v.push(0.0);
+    if e.len() <= 2 {
+        return;
     }
 
-    // Get our iteration count
-    let iterations: i32 = match p.iterations.value().parse::<i32>() {
-        Ok(v) => v * 1000,
-        Err(_) => {
-            alert(368, 265, "Iteration Count Error");
-            return;
-        }
-    };
-    if !(1000..=9999000).contains(&iterations) {
-        alert(368, 265, "Iteration Count Error");
-        return;
-    };
+    let editor = e.pop().unwrap();
+    p.data.remove(&editor);
+    delete_widget(editor);
 
-    // Get our Confidence Level
-    let confidence: f64 = match p.cinterval.value().parse::<f64>() {
-        Ok(v) => v,
-        Err(_) => {
-            alert(368, 265, "Confidence Level Error");
-            return;
-        }
-    };
-    if !(0.0..=100.0).contains(&confidence) {
-        alert(368, 265, "Confidence Level Error");
-        return;
-    };
+    p.columns.scroll_to(0, 0);
+    p.columns.redraw();
+}
 
-    // Get our Z Score Threshold
-    let zthresh: f64 = match p.zthresh.value().parse::<f64>() {
-        Ok(v) => v,
-        Err(_) => {
-            alert(368, 265, "Z Threshold Error");
-            return;
-        }
-    };
+fn clear(p: &mut Parameters) {
+    p.output.buffer().unwrap().set_text("");
+    for (i, mut e) in editors(p).into_iter().enumerate() {
+        e.buffer().unwrap().set_text("");
+        e.set_label(&format!("Data {}", group_name(i)));
+    }
+    p.columns.redraw();
+}
 
-    // Convert to percentage
-    let mut clevel: f64 = (100.0 - confidence) / 100.0;
+// Load a CSV, TSV or spreadsheet file and fill the data columns from the picked columns
+fn open_file(p: &mut Parameters) {
+    let mut dialog = NativeFileChooser::new(NativeFileChooserType::BrowseFile);
+    dialog.set_filter("Delimited Text\t*.{csv,tsv,txt}\nSpreadsheets\t*.{xlsx,xlsm,xls,xlsb,ods}");
+    dialog.show();
 
-    // If it is a two tailed operation, divide the confidence level in half
-    if !p.two_tailed.is_toggled() {
-        clevel /= 2.0;
+    let path = dialog.filename();
+    if path.as_os_str().is_empty() {
+        return;
     }
 
-    // Check for paired or unpaired data
-    let sdmeanresults: Sdmeanresults = if p.paired_data.is_checked() {
-        // For paired data make sure both columns have the same number of elements
-        if a_v.len() != b_v.len() {
-            alert(368, 265, "Data Fields Must Have Same Count for Paired Data");
-            return;
-        }
-
-        paired_data(&a_v, &b_v, iterations, clevel)
+    // Workbooks start on the first sheet with numbers, the dialog can switch sheets
+    let (table, sheets) = if is_workbook(&path) {
+        let sheets = match read_workbook(&path) {
+            Ok(s) => s,
+            Err(e) => {
+                alert(368, 265, &e);
+                return;
+            }
+        };
+        let table = sheets
+            .iter()
+            .find_map(|s| s.table("").ok().filter(|t| t.has_numbers()))
+            .unwrap_or_default();
+        (table, sheets)
     } else {
-        unpaired_data(&a_v, &b_v, iterations, clevel)
+        match std::fs::read_to_string(&path) {
+            Ok(text) => (read_table(&text), Vec::new()),
+            Err(e) => {
+                alert(368, 265, &format!("{}: {}", path.display(), e));
+                return;
+            }
+        }
     };
-
-    // Calculate stats for the data
-    let mean_a = sdmeanresults.amm;
-    let mean_b = sdmeanresults.bmm;
-    let mean_d = sdmeanresults.dmm;
-    let sd_a = sdmeanresults.asm;
-    let sd_b = sdmeanresults.bsm;
-    let sd_d = sdmeanresults.dsm;
-
-    let sdp_a = sd_pop(&a_v, &mean_a);
-    let sdp_b = sd_pop(&b_v, &mean_b);
-
-    let sd_pooled = ((sd_a * sd_a + sd_b * sd_b) / 2.0).sqrt();
-    let d = mean_d / sd_pooled;
-    let sk_a = skewness(&a_v, &mean_a, &sd_a);
-    let sk_b = skewness(&b_v, &mean_b, &sd_b);
-    let kt_a = kurtosis(&a_v, &mean_a, &sd_a);
-    let kt_b = kurtosis(&b_v, &mean_b, &sd_b);
-    let mut f: f64 = 1.0;
-    let mut f_a: usize = a_v.len();
-    let mut f_b: usize = b_v.len();
-    let max_a = a_v.iter().copied().fold(f64::NEG_INFINITY, f64::max);
-    let max_b = b_v.iter().copied().fold(f64::NEG_INFINITY, f64::max);
-    let min_a = a_v.iter().copied().fold(f64::INFINITY, f64::min);
-    let min_b = b_v.iter().copied().fold(f64::INFINITY, f64::min);
-    let (top_values_a, top_counts_a) = count_unique_values(&a_v);
-    let (top_values_b, top_counts_b) = count_unique_values(&b_v);
-
-    if sd_a > sd_b {
-        f = (sd_a * sd_a) / (sd_b * sd_b);
-        f_a = a_v.len();
-        f_b = b_v.len();
+    if table.rows.is_empty() {
+        alert(368, 265, "No Data Found In File");
+        return;
     }
-    if sd_a < sd_b {
-        f = (sd_b * sd_b) / (sd_a * sd_a);
-        f_a = b_v.len();
-        f_b = a_v.len();
+
+    let groups = import_dialog(table, &sheets);
+    if groups.is_empty() {
+        return;
     }
 
-    let f_p = p_from_f(f, f_a - 1, f_b - 1);
-
-    let zcount_a = zcount(&a_v, zthresh);
-    let zcount_b = zcount(&b_v, zthresh);
-
-    out.push_str(&format!("Count A: \t{}\n", a_v.len()));
-    out.push_str(&format!("Count B: \t{}\n", b_v.len()));
-
-    out.push_str(&format!(
-        "\nMin A:    \t{}\n",
-        &science_pretty_format(min_a, 6)
-    ));
-    out.push_str(&format!(
-        "Max A:    \t{}\n",
-        &science_pretty_format(max_a, 6)
-    ));
-    out.push_str(&format!(
-        "\nMin B:    \t{}\n",
-        &science_pretty_format(min_b, 6)
-    ));
-    out.push_str(&format!(
-        "Max B:    \t{}\n",
-        &science_pretty_format(max_b, 6)
-    ));
-
-    out.push_str(&format!(
-        "\n+Z Count A:    \t{}   {}%\n",
-        zcount_a.pluscount,
-        &science_pretty_format(zcount_a.pluspercent, 1),
-    ));
-    out.push_str(&format!(
-        "-Z Count A:    \t{}   {}%\n",
-        zcount_a.minuscount,
-        &science_pretty_format(zcount_a.minuspercent, 1),
-    ));
-    out.push_str(&format!(
-        "\n+Z Count B:    \t{}   {}%\n",
-        zcount_b.pluscount,
-        &science_pretty_format(zcount_b.pluspercent, 1),
-    ));
-    out.push_str(&format!(
-        "-Z Count B:    \t{}   {}%\n",
-        zcount_b.minuscount,
-        &science_pretty_format(zcount_b.minuspercent, 1),
-    ));
-
-    out.push_str("\n************************************\n");
-
-    let mu = sdmeanresults.dmu;
-    let ml = sdmeanresults.dml;
-
-    // Handle one or two tailed data Mean
-    if p.two_tailed.is_toggled() {
-        // Two Tailed
-        let pv = p_from_ci(ml, mu, mean_d, 1.0 - clevel);
-        out.push_str(&format!(
-            "CI Low A: \t{}\n",
-            &science_pretty_format(sdmeanresults.aml, 6)
-        ));
-        out.push_str(&format!(
-            "Mean A: \t{}\n",
-            &science_pretty_format(mean_a, 6)
-        ));
-        out.push_str(&format!(
-            "CI High A: \t{}\n",
-            &science_pretty_format(sdmeanresults.amu, 6)
-        ));
-        out.push_str(&format!(
-            "\nCI Low B: \t{}\n",
-            &science_pretty_format(sdmeanresults.bml, 6)
-        ));
-        out.push_str(&format!(
-            "Mean B: \t{}\n",
-            &science_pretty_format(mean_b, 6)
-        ));
-        out.push_str(&format!(
-            "CI High B: \t{}\n",
-            &science_pretty_format(sdmeanresults.bmu, 6)
-        ));
-        out.push('\n');
+    // Match the number of data columns to the imported groups
+    while editors(p).len() < groups.len() {
+        add_group(p);
+    }
+    while editors(p).len() > groups.len().max(2) {
+        remove_group(p);
+    }
 
-        out.push_str(&format!(
-            "CI Low Diff: \t{}\n",
-            &science_pretty_format(ml, 6)
-        ));
-        out.push_str(&format!(
-            "Mean Diff: \t{}\n",
-            &science_pretty_format(mean_d, 6)
-        ));
-        out.push_str(&format!(
-            "CI High Diff: \t{}\n",
-            &science_pretty_format(mu, 6)
-        ));
-        out.push_str(&format!("\np-Value: \t{}\n", &science_pretty_format(pv, 3)));
+    // Write the values the way the form reads them back, e.g. with a decimal comma
+    let numbers = *p.numbers.borrow();
+    for (i, mut e) in editors(p).into_iter().enumerate() {
+        let (label, text) = match groups.get(i) {
+            Some(g) => (
+                format!("Data {} ({})", group_name(i), g.name),
+                numbers.write(&g.values),
+            ),
+            None => (format!("Data {}", group_name(i)), String::new()),
+        };
+        e.set_label(&label);
+        e.buffer().unwrap().set_text(&text);
+    }
+    p.columns.redraw();
+}
 
-        if pv > clevel {
-            out.push_str("H0 = True \tA ≈ B\n");
-        } else if mean_a > mean_b {
-            out.push_str("H0 = False \tA > B\n");
-        } else {
-            out.push_str("H0 = False \tA < B\n");
+// Preview a table and pick A and B columns, or a group and a value column, empty if cancelled
+// Workbooks also get a sheet choice and an optional cell range
+fn import_dialog(table: Table, sheets: &[Sheet]) -> Vec<Sample> {
+    let mut win = Window::new(200, 200, 520, 430, "Import");
+    let preview = Browser::new(10, 10, 500, 230, "");
+    let mut sheet = Choice::new(130, 250, 150, 24, "Sheet");
+    let mut range = Input::new(360, 250, 100, 24, "Range");
+    let mut layout = Choice::new(130, 285, 220, 24, "Layout");
+    let first = Choice::new(130, 315, 220, 24, "A");
+    let second = Choice::new(130, 345, 220, 24, "B");
+    let mut import_button = Button::new(320, 390, 90, 27, "Import");
+    let mut cancel_button = Button::new(420, 390, 90, 27, "Cancel");
+    win.end();
+    win.make_modal(true);
+
+    if sheets.is_empty() {
+        sheet.hide();
+        range.hide();
+    }
+    for s in sheets {
+        sheet.add(
+            &menu_label(&s.name),
+            Shortcut::None,
+            MenuFlag::Normal,
+            |_| {},
+        );
+    }
+    let start = sheets
+        .iter()
+        .position(|s| s.table("").is_ok_and(|t| t.headers == table.headers))
+        .unwrap_or(0);
+    sheet.set_value(start as i32);
+    range.set_tooltip("Cells to read such as B2:D40, blank for the whole sheet");
+
+    let current = Rc::new(RefCell::new(table));
+
+    // Show the headers and the first rows, cells are plain text
+    let (mut p2, mut f2, mut s2) = (preview.clone(), first.clone(), second.clone());
+    let mut show = move |table: &Table| {
+        p2.clear();
+        p2.set_format_char('\0');
+        p2.set_column_char('\t');
+        p2.set_column_widths(&vec![100; table.headers.len()]);
+        p2.add(&table.headers.join("\t"));
+        for r in table.rows.iter().take(50) {
+            p2.add(&r.join("\t"));
         }
-    } else {
-        // One Tailed
-        let pv = p_from_ci(ml, mu, mean_d, 1.0 - clevel);
 
-        out.push_str(&format!(
-            "CI Low A: \t{}\n",
-            &science_pretty_format(sdmeanresults.aml, 6)
-        ));
-        out.push_str(&format!(
-            "Mean A: \t{}\n",
-            &science_pretty_format(mean_a, 6)
-        ));
-        out.push_str(&format!(
-            "CI High A: \t{}\n",
-            &science_pretty_format(sdmeanresults.amu, 6)
-        ));
-        out.push_str(&format!(
-            "\nCI Low B: \t{}\n",
-            &science_pretty_format(sdmeanresults.bml, 6)
-        ));
-        out.push_str(&format!(
-            "Mean B: \t{}\n",
-            &science_pretty_format(mean_b, 6)
-        ));
-        out.push_str(&format!(
-            "CI High B: \t{}\n",
-            &science_pretty_format(sdmeanresults.bmu, 6)
-        ));
-        out.push('\n');
-
-        if mean_a > mean_b {
-            out.push_str(&format!(
-                "CI Low Diff: \t{}\n",
-                &science_pretty_format(ml, 6)
-            ));
-            out.push_str(&format!(
-                "Mean Diff: \t{}\n",
-                &science_pretty_format(mean_d, 6)
-            ));
-            out.push_str(&format!("\np-Value: \t{}\n", &science_pretty_format(pv, 3)));
-            if pv > clevel {
-                out.push_str("H0 = True \tA ≈ B\n");
-            } else {
-                out.push_str("H0 = False \tA > B\n");
-            }
-        } else {
-            out.push_str(&format!(
-                "Mean Diff: \t{}\n",
-                &science_pretty_format(mean_d, 6)
-            ));
-            out.push_str(&format!(
-                "CI High Diff: \t{}\n",
-                &science_pretty_format(mu, 6)
-            ));
-            out.push_str(&format!("\np-Value: \t{}\n", &science_pretty_format(pv, 3)));
-            if pv > clevel {
-                out.push_str("H0 = True \tA ≈ B\n");
-            } else {
-                out.push_str("H0 = False \tA < B\n");
+        f2.clear();
+        s2.clear();
+        for h in &table.headers {
+            f2.add(&menu_label(h), Shortcut::None, MenuFlag::Normal, |_| {});
+            s2.add(&menu_label(h), Shortcut::None, MenuFlag::Normal, |_| {});
+        }
+        f2.set_value(0);
+        s2.set_value(if table.headers.len() > 1 { 1 } else { 0 });
+    };
+    show(&current.borrow());
+
+    // Reload the table when the sheet or range changes
+    let reload = {
+        let (sheets, current, sheet, range) = (
+            sheets.to_vec(),
+            current.clone(),
+            sheet.clone(),
+            range.clone(),
+        );
+        let mut show = show.clone();
+        move || {
+            let s = match sheets.get(sheet.value().max(0) as usize) {
+                Some(s) => s,
+                None => return,
+            };
+            match s.table(&range.value()) {
+                Ok(t) => {
+                    show(&t);
+                    *current.borrow_mut() = t;
+                }
+                Err(e) => alert(368, 265, &e),
             }
         }
-    }
+    };
+    let mut r2 = reload.clone();
+    sheet.set_callback(move |_| r2());
+    let mut r3 = reload.clone();
+    range.set_trigger(CallbackTrigger::EnterKeyAlways);
+    range.set_callback(move |_| r3());
+
+    layout.add_choice("Columns A and B|Group and Value Columns");
+    layout.set_value(0);
+
+    let (mut f3, mut s3) = (first.clone(), second.clone());
+    layout.set_callback(move |l| {
+        let long = l.value() == 1;
+        f3.set_label(if long { "Group Column" } else { "A" });
+        s3.set_label(if long { "Value Column" } else { "B" });
+        f3.parent().unwrap().redraw();
+    });
+
+    let result: Rc<RefCell<Vec<Sample>>> = Rc::new(RefCell::new(Vec::new()));
+
+    let (r4, c2, mut w2) = (result.clone(), current.clone(), win.clone());
+    import_button.set_callback(move |_| {
+        let t2 = c2.borrow();
+        let (a, b) = (
+            first.value().max(0) as usize,
+            second.value().max(0) as usize,
+        );
+        if a >= t2.headers.len() || b >= t2.headers.len() {
+            alert(368, 265, "Need Two Groups With Numeric Values");
+            return;
+        }
+        let groups = if layout.value() == 1 {
+            t2.groups(a, b)
+        } else {
+            [a, b]
+                .iter()
+                .map(|c| Sample {
+                    name: t2.headers[*c].clone(),
+                    values: t2.column(*c),
+                })
+                .collect()
+        };
 
-    out.push_str(&format!(
-        "\n% Change: \t{}\n",
-        &science_pretty_format(per_change(&mean_a, &mean_b), 1)
-    ));
+        if groups.len() < 2 || groups.iter().any(|g| g.values.iter().all(|v| v.is_nan())) {
+            alert(368, 265, "Need Two Groups With Numeric Values");
+            return;
+        }
+        *r4.borrow_mut() = groups;
+        w2.hide();
+    });
 
-    out.push_str("\n************************************\n");
+    let mut w3 = win.clone();
+    cancel_button.set_callback(move |_| w3.hide());
 
-    let su = sdmeanresults.dsu;
-    let sl = sdmeanresults.dsl;
+    win.show();
+    while win.shown() {
+        wait();
+    }
 
-    // Handle one or two tailed data SD
-    if p.two_tailed.is_toggled() {
-        // Two Tailed
-        let pv = p_from_ci(sl, su, sd_d, 1.0 - clevel);
+    result.take()
+}
 
-        out.push_str(&format!(
-            "CI Low A:     \t{}\n",
-            &science_pretty_format(sdmeanresults.asl, 6)
-        ));
-        out.push_str(&format!(
-            "SD A:     \t{}\n",
-            &science_pretty_format(sd_a, 6)
-        ));
-        out.push_str(&format!(
-            "CI High A:     \t{}\n",
-            &science_pretty_format(sdmeanresults.asu, 6)
-        ));
-        out.push_str(&format!(
-            "\nCI Low B:     \t{}\n",
-            &science_pretty_format(sdmeanresults.bsl, 3)
-        ));
-        out.push_str(&format!(
-            "SD B:     \t{}\n",
-            &science_pretty_format(sd_b, 3)
-        ));
-        out.push_str(&format!(
-            "CI High B:     \t{}\n",
-            &science_pretty_format(sdmeanresults.bsu, 3)
-        ));
-        out.push('\n');
+// Menu paths treat these characters specially
+fn menu_label(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('/', "\\/")
+        .replace('&', "&&")
+}
 
-        out.push_str(&format!(
-            "CI Low Diff: \t{}\n",
-            &science_pretty_format(sl, 6)
-        ));
-        out.push_str(&format!("SD Diff: \t{}\n", &science_pretty_format(sd_d, 6)));
-        out.push_str(&format!(
-            "CI High Diff: \t{}\n",
-            &science_pretty_format(su, 6)
-        ));
-        out.push_str(&format!("\np-Value: \t{}\n", &science_pretty_format(pv, 3)));
+// Separator choices in the number format dialog, in menu order
+const DECIMALS: [char; 2] = ['.', ','];
+const THOUSANDS: [char; 3] = ['.', ',', '\''];
+const DELIMITERS: [char; 4] = [',', ';', '\t', '\n'];
+
+// Pick the decimal mark, thousands separator and delimiter, with a preview of the parsed data
+fn number_format_dialog(p: &Parameters) {
+    let mut win = Window::new(200, 200, 520, 420, "Number Format");
+    let mut auto = CheckButton::new(110, 10, 150, 22, "Auto Detect");
+    let mut decimal = Choice::new(110, 40, 150, 24, "Decimal");
+    let mut thousands = Choice::new(110, 70, 150, 24, "Thousands");
+    let mut delimiter = Choice::new(110, 100, 150, 24, "Delimiter");
+    let mut preview = Browser::new(10, 140, 500, 230, "");
+    let mut ok_button = Button::new(320, 380, 90, 27, "OK");
+    let mut cancel_button = Button::new(420, 380, 90, 27, "Cancel");
+    win.end();
+    win.make_modal(true);
+
+    decimal.add_choice("Point|Comma");
+    thousands.add_choice("None|Point|Comma|Apostrophe");
+    delimiter.add_choice("Comma|Semicolon|Tab|Newline");
+    preview.set_format_char('\0');
+    preview.set_column_char('\t');
+
+    let texts: Vec<String> = editors(p)
+        .iter()
+        .map(|e| e.buffer().unwrap().text())
+        .collect();
 
-        if pv > clevel {
-            out.push_str("H0 = True \tA ≈ B\n");
-        } else if sd_a > sd_b {
-            out.push_str("H0 = False \tA > B\n");
-        } else {
-            out.push_str("H0 = False \tA < B\n");
-        }
-    } else {
-        // One Tailed
-        let pv = p_from_ci(sl, su, sd_d, 1.0 - clevel);
+    // Read the widgets back into a format
+    let (a2, d2, t2, l2) = (
+        auto.clone(),
+        decimal.clone(),
+        thousands.clone(),
+        delimiter.clone(),
+    );
+    let current = move || Numberformat {
+        auto: a2.is_checked(),
+        decimal: DECIMALS[d2.value().max(0) as usize],
+        thousands: match t2.value() {
+            v if v > 0 => Some(THOUSANDS[v as usize - 1]),
+            _ => None,
+        },
+        delimiter: DELIMITERS[l2.value().max(0) as usize],
+    };
 
-        out.push_str(&format!(
-            "CI Low A:     \t{}\n",
-            &science_pretty_format(sdmeanresults.asl, 6)
-        ));
-        out.push_str(&format!(
-            "SD A:     \t{}\n",
-            &science_pretty_format(sd_a, 6)
-        ));
-        out.push_str(&format!(
-            "CI High A:     \t{}\n",
-            &science_pretty_format(sdmeanresults.asu, 6)
-        ));
-        out.push_str(&format!(
-            "\nCI Low B:     \t{}\n",
-            &science_pretty_format(sdmeanresults.bsl, 3)
-        ));
-        out.push_str(&format!(
-            "SD B:     \t{}\n",
-            &science_pretty_format(sd_b, 3)
-        ));
-        out.push_str(&format!(
-            "CI High B:     \t{}\n",
-            &science_pretty_format(sdmeanresults.bsu, 3)
-        ));
-        out.push('\n');
-
-        if sd_a > sd_b {
-            out.push_str(&format!(
-                "CI Low Diff: \t{}\n",
-                &science_pretty_format(sl, 6)
-            ));
-            out.push_str(&format!("SD Diff: \t{}\n", &science_pretty_format(sd_d, 6)));
-            out.push_str(&format!("\np-Value: \t{}\n", &science_pretty_format(pv, 3)));
-            if pv > clevel {
-                out.push_str("H0 = True \tA ≈ B\n");
+    // Show a format in the widgets, auto shows what was detected across all the columns
+    let (mut d3, mut t3, mut l3) = (decimal.clone(), thousands.clone(), delimiter.clone());
+    let all = texts.clone();
+    let mut show = move |f: &Numberformat| {
+        let auto = f.auto;
+        let f = f.resolve_all(&all);
+        let index = |list: &[char], c: char| list.iter().position(|x| *x == c).unwrap_or(0) as i32;
+        d3.set_value(index(&DECIMALS, f.decimal));
+        t3.set_value(f.thousands.map_or(0, |c| index(&THOUSANDS, c) + 1));
+        l3.set_value(index(&DELIMITERS, f.delimiter));
+        for c in [&mut d3, &mut t3, &mut l3] {
+            if auto {
+                c.deactivate();
             } else {
-                out.push_str("H0 = False \tA > B\n");
-            }
-        } else {
-            out.push_str(&format!("SD Diff: \t{}\n", &science_pretty_format(sd_d, 6)));
-            out.push_str(&format!(
-                "CI High Diff: \t{}\n",
-                &science_pretty_format(su, 6)
-            ));
-            out.push_str(&format!("\np-Value: \t{}\n", &science_pretty_format(pv, 3)));
-            if pv > clevel {
-                out.push_str("H0 = True \tA ≈ B\n");
-            } else {
-                out.push_str("H0 = False \tA < B\n");
+                c.activate();
             }
         }
-    }
-
-    out.push_str(&format!(
-        "\n% Change: \t{}\n",
-        &science_pretty_format(per_change(&sd_a, &sd_b), 1)
-    ));
+    };
 
-    out.push_str("\n************************************\n");
+    // Parsed values side by side, one column per group, rejected tokens in brackets
+    let mut refresh = {
+        let current = current.clone();
+        let mut preview = preview.clone();
+        move || {
+            let f = current();
+            preview.clear();
+            if let Err(e) = f.check() {
+                preview.add(&e);
+                return;
+            }
 
-    let var_a = sdp_a * sdp_a;
-    let var_b = sdp_b * sdp_b;
+            let f = f.resolve_all(&texts);
+            let parsed: Vec<Parsed> = texts.iter().map(|t| csv_parse(t, &f)).collect();
+            let names: Vec<String> = (0..texts.len())
+                .map(|i| format!("Data {}", group_name(i)))
+                .collect();
+            preview.set_column_widths(&vec![100; texts.len()]);
+            preview.add(&names.join("\t"));
+
+            let rows = parsed.iter().map(|x| x.slots.len()).max().unwrap_or(0);
+            for r in 0..rows.min(200) {
+                let cells: Vec<String> = parsed
+                    .iter()
+                    .map(|x| match x.slots.get(r) {
+                        Some(v) if !v.is_nan() => v.to_string(),
+                        Some(_) => "NA".to_string(),
+                        None => String::new(),
+                    })
+                    .collect();
+                preview.add(&cells.join("\t"));
+            }
+            for (i, x) in parsed.iter().enumerate() {
+                for t in &x.rejected {
+                    preview.add(&format!("Rejected in {}: [{}]", names[i], t.token));
+                }
+            }
+        }
+    };
 
-    out.push_str(&format!(
-        "Variance A:    \t{}\n",
-        &science_pretty_format(var_a, 6)
-    ));
-    out.push_str(&format!(
-        "Variance B:    \t{}\n",
-        &science_pretty_format(var_b, 6)
-    ));
+    auto.set_checked(p.numbers.borrow().auto);
+    show(&p.numbers.borrow());
+    refresh();
+
+    // Turning auto off starts from what was detected
+    let mut r2 = refresh.clone();
+    let current2 = current.clone();
+    auto.set_callback(move |_| {
+        show(&current2());
+        r2();
+    });
+    for c in [&mut decimal, &mut thousands, &mut delimiter] {
+        let mut r3 = refresh.clone();
+        c.set_callback(move |_| r3());
+    }
 
-    out.push_str("\n************************************\n");
+    let (numbers, mut w2) = (p.numbers.clone(), win.clone());
+    ok_button.set_callback(move |_| {
+        let f = current();
+        if let Err(e) = f.check() {
+            alert(368, 265, &e);
+            return;
+        }
+        *numbers.borrow_mut() = f;
+        w2.hide();
+    });
 
-    let med_a = median(&a_v);
-    let med_b = median(&b_v);
+    let mut w3 = win.clone();
+    cancel_button.set_callback(move |_| w3.hide());
 
-    out.push_str(&format!(
-        "Median A:    \t{}\n",
-        &science_pretty_format(med_a, 6)
-    ));
+    win.show();
+    while win.shown() {
+        wait();
+    }
+}
 
-    out.push_str(&format!(
-        "Median B:    \t{}\n",
-        &science_pretty_format(med_b, 6)
-    ));
+// Handle Calculate button
+fn calculate(p: &mut Parameters) {
+    // Get the CSV data out of the data fields
+    let mut samples: Vec<Sample> = Vec::new();
+    let mut warnings = String::new();
+    let mut rejected = 0;
+
+    // Auto detects one format across all the columns and says which
+    let editors = editors(p);
+    let texts: Vec<String> = editors.iter().map(|e| e.buffer().unwrap().text()).collect();
+    let numbers = p.numbers.borrow().resolve_all(&texts);
+    if p.numbers.borrow().auto {
+        warnings.push_str(&format!(
+            "Number Format: \t{} (detected)\n",
+            numbers.describe()
+        ));
+    }
 
-    out.push_str(&format!(
-        "\n% Change: \t{}\n",
-        &science_pretty_format(per_change(&med_a, &med_b), 1)
-    ));
+    for (i, (e, text)) in editors.iter().zip(&texts).enumerate() {
+        let parsed = csv_parse(text, &numbers);
 
-    out.push_str("\n************************************\n");
+        highlight(e, &parsed.rejected, &parsed.suspicious);
+        if !parsed.suspicious.is_empty() {
+            warnings.push_str(&suspicious_tokens(&group_name(i), &parsed.suspicious));
+        }
+        if !parsed.rejected.is_empty() {
+            warnings.push_str(&rejected_tokens(&group_name(i), &parsed.rejected));
+            rejected += parsed.rejected.len();
+        }
 
-    let mut biggest_count_a = 0;
-    let mut biggest_count_b = 0;
-    let mut biggest_value_a: f64 = 0.0;
-    let mut biggest_value_b: f64 = 0.0;
+        // Paired data keeps a slot for every cell so missing values do not shift the rows
+        let mut values = if p.paired_data.is_checked() {
+            parsed.slots
+        } else {
+            parsed.values
+        };
 
-    for (i, _) in top_values_a.iter().enumerate() {
-        if top_counts_a[i] > biggest_count_a {
-            biggest_count_a = top_counts_a[i];
-            biggest_value_a = top_values_a[i];
+        if values.iter().all(|v| v.is_nan()) {
+            if !values.is_empty() || !parsed.rejected.is_empty() {
+                alert(368, 265, &format!("No Numbers In Data {}", group_name(i)));
+                return;
+            }
+            e.buffer().unwrap().set_text("0.0");
+            values.push(0.0);
         }
-    }
 
-    for (i, _) in top_values_b.iter().enumerate() {
-        if top_counts_b[i] > biggest_count_b {
-            biggest_count_b = top_counts_b[i];
-            biggest_value_b = top_values_b[i];
-        }
+        samples.push(Sample {
+            name: group_name(i),
+            values,
+        });
     }
 
-    if biggest_count_a > 1 {
-        out.push_str(&format!(
-            "Mode A:    \t{}\n",
-            &science_pretty_format(biggest_value_a, 6)
-        ));
-    } else {
-        out.push_str("Mode A:    \tNONE\n");
+    // Strict mode refuses to calculate with anything rejected
+    if p.strict.is_checked() && rejected > 0 {
+        p.output.buffer().unwrap().set_text(&warnings);
+        alert(
+            368,
+            265,
+            &format!("{} Rejected Tokens, Fix The Marked Input", rejected),
+        );
+        return;
     }
 
-    if biggest_count_b > 1 {
-        out.push_str(&format!(
-            "Mode B:    \t{}\n",
-            &science_pretty_format(biggest_value_b, 6)
-        ));
-    } else {
-        out.push_str("Mode B:    \tNONE\n");
-    }
+    let settings = match form_settings(p) {
+        Ok(s) => s,
+        Err(e) => {
+            alert(368, 265, &e);
+            return;
+        }
+    };
 
-    out.push_str("\n************************************\n");
-
-    out.push_str(&format!("Cohen's d: \t{}\n", &science_pretty_format(d, 2)));
-
-    out.push_str("\n************************************\n");
-    out.push_str(&format!(
-        "F-Test:   \t{}\n",
-        &science_pretty_format(1.0 / f, 4)
-    ));
-    out.push_str(&format!(
-        "\np-Value: \t{}\n",
-        &science_pretty_format(f_p * 2.0, 4)
-    ));
-    if f_p * 2.0 <= clevel {
-        out.push_str("Sig:       \tSignificant\n");
+    // Two columns get the full A / B report, more get the k sample report
+    let out = if samples.len() == 2 {
+        compare(&samples[0], &samples[1], &settings).map(|c| {
+            (
+                two_sample(&c),
+                charts(&c),
+                comparison_document(&c),
+                c.settings.clone(),
+            )
+        })
     } else {
-        out.push_str("Sig:       \tNot Significant\n");
-    }
-
-    out.push_str("\n************************************\n");
-
-    let se_a = sd_a / (a_v.len() as f64).sqrt();
-    let se_b = sd_b / (b_v.len() as f64).sqrt();
-
-    out.push_str(&format!(
-        "SE A:     \t{}\n",
-        &science_pretty_format(se_a, 6)
-    ));
-    out.push_str(&format!(
-        "SE B:     \t{}\n",
-        &science_pretty_format(se_b, 6)
-    ));
-
-    out.push_str("\n************************************\n");
-
-    out.push_str(&format!(
-        "Skewness A:    \t{}\n",
-        &science_pretty_format(sk_a, 3)
-    ));
-    out.push_str(&format!(
-        "Skewness B:    \t{}\n",
-        &science_pretty_format(sk_b, 3)
-    ));
-    out.push_str(&format!(
-        "\nKurtosis A:    \t{}\n",
-        &science_pretty_format(kt_a, 3)
-    ));
-    out.push_str(&format!(
-        "Kurtosis B:    \t{}\n",
-        &science_pretty_format(kt_b, 3)
-    ));
-
-    out.push_str("\n************************************\n");
-
-    // Check for paired correlation data
-    if p.paired_data.is_checked() {
-        // Perform correlation calculations
-        if a_v.len() > 1 {
-            let r = r_value(rankify(&a_v), rankify(&b_v));
-
-            out.push_str(&format!(
-                "Spearman's ρ: \t{}\n",
-                &science_pretty_format(r, 2)
-            ));
-
-            let cstring = match r {
-                r if r == 0.0 => "None",
-                r if (r - 1.0).abs() < f64::EPSILON => "Perfect Pos",
-                r if (r - -1.0).abs() < f64::EPSILON => "Perfect Neg",
-                r if r > 0.0 && r < 0.3 => "Weak Pos",
-                r if r >= 0.3 && r < 0.7 => "Moderate Pos",
-                r if r >= 0.7 && r < 1.0 => "Strong Pos",
-                r if r < 0.0 && r > -0.3 => "Weak Neg",
-                r if r <= -0.3 && r > -0.7 => "Moderate Neg",
-                r if r <= -0.7 && r > -1.00 => "Strong Neg",
-                _ => "",
-            };
+        compare_many(&samples, &settings).map(|m| {
+            (
+                multi_sample(&m),
+                multi_charts(&m),
+                multi_document(&m),
+                m.settings.clone(),
+            )
+        })
+    };
 
-            out.push_str(&format!("Corr:      \t{}\n", &cstring));
+    // The settings that come back hold the seed used
+    let (out, charts, document, used) = match out {
+        Ok(v) => v,
+        Err(e) => {
+            alert(368, 265, &e);
+            return;
+        }
+    };
 
-            let dof = a_v.len() as f64 - 2.0;
-            let tr = r / ((1.0 - r * r) / dof).sqrt();
-            let pr = p_from_t(tr, dof);
+    // Send out to the main text box, rejected tokens first so they are not missed, then the seed in use
+    if let Some(seed) = used.seed {
+        let fixed = if p.seed.borrow().is_empty() {
+            ""
+        } else {
+            " (fixed in Edit/Random Seed)"
+        };
+        warnings.push_str(&format!("Seed:      \t{}{}\n", seed, fixed));
+    }
+    if !warnings.is_empty() {
+        warnings.push_str("\n************************************\n");
+    }
+    p.output
+        .buffer()
+        .unwrap()
+        .set_text(&format!("{}{}", warnings, out));
+
+    // Refresh the chart list in the plot window
+    p.plot_choice.clear();
+    for c in &charts {
+        p.plot_choice.add_choice(&c.title);
+    }
+    p.plot_choice.set_value(0);
+    *p.charts.borrow_mut() = charts;
+    *p.document.borrow_mut() = document;
+    *p.last.borrow_mut() = used;
+    p.plot_area.redraw();
+}
 
-            out.push_str(&format!("\np-Value: \t{}\n", &science_pretty_format(pr, 3)));
+// Read the calculation settings from the form, returning the alert text for the first bad value
+fn form_settings(p: &Parameters) -> Result<Settings, String> {
+    // Get our iteration count
+    let iterations: i32 = match p
+        .iterations
+        .value()
+        .parse::<i32>()
+        .map(|v| v.checked_mul(1000))
+    {
+        Ok(Some(v)) => v,
+        _ => return Err("Iteration Count Error".to_string()),
+    };
 
-            if pr <= clevel {
-                out.push_str("Sig:       \tSignificant\n");
-            } else {
-                out.push_str("Sig:       \tNot Significant\n");
-            }
+    // Get our Confidence Level
+    let confidence: f64 = match p.cinterval.value().parse::<f64>() {
+        Ok(v) => v,
+        Err(_) => return Err("Confidence Level Error".to_string()),
+    };
 
-            out.push_str("\n************************************\n");
+    // Get our Z Score Threshold
+    let zthresh: f64 = match p.zthresh.value().parse::<f64>() {
+        Ok(v) => v,
+        Err(_) => return Err("Z Threshold Error".to_string()),
+    };
 
-            out.push_str(&format!(
-                "R²: \t{}\n",
-                &science_pretty_format(r2_value(&a_v, &b_v), 3)
-            ));
+    Ok(Settings {
+        paired: p.paired_data.is_checked(),
+        two_tailed: p.two_tailed.is_toggled(),
+        confidence,
+        iterations,
+        zthresh,
+        adjust: match p.adjust.value() {
+            1 => Adjust::Bonferroni,
+            2 => Adjust::Holm,
+            3 => Adjust::BenjaminiHochberg,
+            _ => Adjust::None,
+        },
+        missing: match p.missing.value() {
+            1 => Missing::Pairwise,
+            _ => Missing::Listwise,
+        },
+        seed: match p.seed.borrow().as_str() {
+            "" => None,
+            seed => Some(parse_seed(seed)?),
+        },
+    })
+}
 
-            out.push_str("\n************************************\n");
+// Compare every metric of a long format file of metric, group, value and pair id with the form settings
+fn batch_file(p: &Parameters) {
+    let settings = match form_settings(p) {
+        Ok(s) => s,
+        Err(e) => {
+            alert(368, 265, &e);
+            return;
         }
-    }
+    };
 
-    // Find and count unique values
-    out.push_str("Unique A Value Counts\n\n");
+    let mut dialog = NativeFileChooser::new(NativeFileChooserType::BrowseFile);
+    dialog.set_filter("Delimited Text\t*.{csv,tsv,txt}\nSpreadsheets\t*.{xlsx,xlsm,xls,xlsb,ods}");
+    dialog.show();
 
-    for (i, _) in top_values_a.iter().enumerate() {
-        out.push_str(&format!(
-            "{}\t{}\n",
-            &science_pretty_format(top_values_a[i], 6),
-            top_counts_a[i]
-        ));
+    let path = dialog.filename();
+    if path.as_os_str().is_empty() {
+        return;
     }
 
-    out.push_str("\n\nUnique B Value Counts\n\n");
+    // Workbooks use the first sheet with numbers
+    let table = if is_workbook(&path) {
+        match read_workbook(&path) {
+            Ok(sheets) => sheets
+                .iter()
+                .find_map(|s| s.table("").ok().filter(|t| t.has_numbers()))
+                .unwrap_or_default(),
+            Err(e) => {
+                alert(368, 265, &e);
+                return;
+            }
+        }
+    } else {
+        match std::fs::read_to_string(&path) {
+            Ok(text) => {
+                let mut table = read_table(&text);
+                if !p.numbers.borrow().auto {
+                    table.format = *p.numbers.borrow();
+                }
+                table
+            }
+            Err(e) => {
+                alert(368, 265, &format!("{}: {}", path.display(), e));
+                return;
+            }
+        }
+    };
 
-    for (i, _) in top_values_b.iter().enumerate() {
-        out.push_str(&format!(
-            "{}\t{}\n",
-            &science_pretty_format(top_values_b[i], 6),
-            top_counts_b[i]
-        ));
-    }
+    let b = match batch(&table, &settings) {
+        Ok(b) => b,
+        Err(e) => {
+            alert(368, 265, &e);
+            return;
+        }
+    };
 
-    // Send out to the main text box
-    p.output.buffer().unwrap().set_text(&out);
+    // Rejected values first so they are not missed, strict mode stops there
+    let mut warnings = String::new();
+    if !b.rejected.is_empty() {
+        warnings = rejected_tokens("Values", &b.rejected);
+        if p.strict.is_checked() {
+            p.output.buffer().unwrap().set_text(&warnings);
+            alert(
+                368,
+                265,
+                &format!("{} Rejected Tokens, Fix The Input File", b.rejected.len()),
+            );
+            return;
+        }
+        warnings.push_str("\n************************************\n");
+    }
+    p.output
+        .buffer()
+        .unwrap()
+        .set_text(&format!("{}{}", warnings, batch_report(&b)));
+
+    // A batch has no charts, the summary table goes to the exports
+    let mut p = p.clone();
+    p.charts.borrow_mut().clear();
+    p.plot_choice.clear();
+    p.plot_area.redraw();
+    *p.document.borrow_mut() = batch_document(&b);
+    *p.last.borrow_mut() = b.settings;
 }
 
-// Paired data
-fn paired_data(a_v: &[f64], b_v: &[f64], iterations: i32, clevel: f64) -> Sdmeanresults {
-    let a = ci(a_v, iterations, clevel);
-    let b = ci(b_v, iterations, clevel);
-
-    let mut cvalues: Vec<f64> = Vec::new();
+// Mark rejected tokens and suspicious lines in a data column
+fn highlight(e: &TextEditor, rejected: &[Rejected], suspicious: &[Rejected]) {
+    let len = e.buffer().unwrap().length() as usize;
+    let mut styles = vec![b'A'; len];
 
-    let l = a_v.len();
-
-    for i in 0..l {
-        cvalues.push(b_v[i as usize] - a_v[i as usize]);
+    for r in suspicious {
+        styles[r.start..r.end].fill(b'C');
+    }
+    for r in rejected {
+        styles[r.start..r.end].fill(b'B');
     }
 
-    let c = ci(&cvalues, iterations, clevel);
-
-    Sdmeanresults {
-        amu: a.mu,
-        aml: a.ml,
-        amm: a.mm,
-        asu: a.su,
-        asl: a.sl,
-        asm: a.sm,
-        bmu: b.mu,
-        bml: b.ml,
-        bmm: b.mm,
-        bsu: b.su,
-        bsl: b.sl,
-        bsm: b.sm,
-        dmu: c.mu,
-        dml: c.ml,
-        dmm: c.mm,
-        dsu: c.su,
-        dsl: c.sl,
-        dsm: c.sm,
+    if let Some(mut style) = e.style_buffer() {
+        style.set_text(&String::from_utf8(styles).unwrap());
     }
+    e.clone().redraw();
 }
 
-// Unpaired data
-fn unpaired_data(a_v: &[f64], b_v: &[f64], iterations: i32, clevel: f64) -> Sdmeanresults {
-    let a = ci(a_v, iterations, clevel);
-    let b = ci(b_v, iterations, clevel);
-
-    Sdmeanresults {
-        amu: a.mu,
-        aml: a.ml,
-        amm: a.mm,
-        asu: a.su,
-        asl: a.sl,
-        asm: a.sm,
-        bmu: b.mu,
-        bml: b.ml,
-        bmm: b.mm,
-        bsu: b.su,
-        bsl: b.sl,
-        bsm: b.sm,
-        dmu: b.mu - a.ml,
-        dml: b.ml - a.mu,
-        dmm: b.mm - a.mm,
-        dsu: b.su - a.sl,
-        dsl: b.sl - a.su,
-        dsm: b.sm - a.sm,
+// Draw the chart picked in the plot window
+fn draw_chart(p: &Parameters, w: &mut Frame) {
+    let charts = p.charts.borrow();
+
+    match charts.get(p.plot_choice.value() as usize) {
+        Some(chart) => draw(
+            chart,
+            &mut Fltkcanvas {
+                x: w.x() as f64,
+                y: w.y() as f64,
+            },
+            w.w() as f64,
+            w.h() as f64,
+        ),
+        None => {
+            fltk::draw::set_draw_color(fltk::enums::Color::Black);
+            fltk::draw::draw_text2(
+                "Calculate to see charts",
+                w.x(),
+                w.y(),
+                w.w(),
+                w.h(),
+                Align::Center,
+            );
+        }
     }
 }
 
-// Calculate a bootstrapped mean and confidence interval for an array of data
-fn ci(v: &[f64], iterations: i32, clevel: f64) -> CIresults {
-    let mut tmp: Vec<f64> = Vec::new();
-    let mut means: Vec<f64> = Vec::new();
-    let mut sds: Vec<f64> = Vec::new();
+// Save the chart picked in the plot window as SVG or PNG
+fn save_plot(p: &Parameters) {
+    let charts = p.charts.borrow();
 
-    let len = v.len();
-
-    for _i in 0..iterations {
-        tmp.clear();
-        for _j in 0..len {
-            tmp.push(v[rand::thread_rng().gen_range(0..len)]);
+    let chart = match charts.get(p.plot_choice.value() as usize) {
+        Some(c) => c,
+        None => {
+            alert(368, 265, "Calculate to see charts");
+            return;
         }
-        let m: f64 = mean(&tmp);
-        means.push(m);
-        sds.push(sd_sample(&tmp, &m));
-    }
+    };
+
+    let mut dialog = NativeFileChooser::new(NativeFileChooserType::BrowseSaveFile);
+    dialog.set_option(NativeFileChooserOptions::SaveAsConfirm);
+    dialog.set_filter("SVG\t*.svg\nPNG\t*.png");
+    dialog.set_preset_file(&format!("{}.svg", chart_file_name(chart)));
+    dialog.show();
 
-    means.sort_by(cmp_f64);
-    sds.sort_by(cmp_f64);
+    let mut path = dialog.filename();
+    if path.as_os_str().is_empty() {
+        return;
+    }
+    if path.extension().is_none() {
+        path.set_extension(if dialog.filter_value() == 1 {
+            "png"
+        } else {
+            "svg"
+        });
+    }
 
-    CIresults {
-        mm: (means[(iterations / 2) as usize]),
-        ml: (means[(iterations as f64 * clevel) as usize]),
-        mu: (means[(iterations as f64 * (1.0 - clevel)) as usize]),
-        sm: (sds[(iterations / 2) as usize]),
-        sl: (sds[(iterations as f64 * clevel) as usize]),
-        su: (sds[(iterations as f64 * (1.0 - clevel)) as usize]),
+    if let Err(e) = save_chart(chart, &path, EXPORT_W, EXPORT_H) {
+        alert(368, 265, &e);
     }
 }
 
-// Convert CSV from the main windows to arrays of floats, also clean up stray whitespace
-fn csv_split(inp: &str) -> Vec<f64> {
-    let mut values: Vec<f64> = Vec::new();
-
-    let clean_inp: String = inp
-        .replace("\n", ",")
-        .chars()
-        .filter(|c| !c.is_whitespace())
-        .collect();
-
-    let fields = clean_inp.split(',');
-
-    for f in fields {
-        match f.parse::<f64>() {
-            Ok(v) => values.push(v),
-            Err(_) => continue,
-        };
+// Copy the block of the output around the selection or the cursor as tab separated lines
+fn copy_section(p: &Parameters) {
+    let buf = p.output.buffer().unwrap();
+    let text = buf.text();
+    if text.trim().is_empty() {
+        alert(368, 265, "Calculate before copying a section");
+        return;
     }
 
-    values
+    let pos = match buf.selection_position() {
+        Some((start, _)) => start,
+        None => p.output.insert_position(),
+    };
+    copy(&section_tsv(&text, pos.max(0) as usize));
 }
 
-// Calculate mean
-fn mean(vec: &[f64]) -> f64 {
-    let sum: f64 = Iterator::sum(vec.iter());
-    sum / vec.len() as f64
+// The column name shown after the group in a data column label, empty when there is none
+fn column_name(label: &str) -> String {
+    match label.split_once(" (") {
+        Some((_, name)) => name.strip_suffix(')').unwrap_or(name).to_string(),
+        None => String::new(),
+    }
 }
 
-// Calculate median
-fn median(vec: &[f64]) -> f64 {
-    let mut v = vec.to_owned();
-
-    v.sort_by(cmp_f64);
-    v[vec.len() / 2]
-}
+// Save the data columns, the form and the last results, the seed is the one behind the results
+fn save_session_dialog(p: &Parameters, menu: &mut MenuBar) {
+    let mut settings = match form_settings(p) {
+        Ok(s) => s,
+        Err(e) => {
+            alert(368, 265, &e);
+            return;
+        }
+    };
+    let results = p.output.buffer().unwrap().text();
+    if !results.trim().is_empty() {
+        settings.seed = p.last.borrow().seed;
+    }
 
-// Find uniques and count them
-fn count_unique_values(vec: &[f64]) -> (Vec<f64>, Vec<i32>) {
-    let mut v = vec.to_owned();
-    let mut pv: f64;
-    let mut count: i32;
-    let mut values_out: Vec<f64> = Vec::new();
-    let mut counts_out: Vec<i32> = Vec::new();
+    let mut dialog = NativeFileChooser::new(NativeFileChooserType::BrowseSaveFile);
+    dialog.set_option(NativeFileChooserOptions::SaveAsConfirm);
+    dialog.set_filter("TOML\t*.toml\nJSON\t*.json");
+    dialog.set_preset_file("session.toml");
+    dialog.show();
 
-    v.sort_by(cmp_f64);
+    let mut path = dialog.filename();
+    if path.as_os_str().is_empty() {
+        return;
+    }
+    if path.extension().is_none() {
+        path.set_extension(if dialog.filter_value() == 1 {
+            "json"
+        } else {
+            "toml"
+        });
+    }
 
-    count = 0;
-    pv = v[0];
+    let session = Session {
+        settings,
+        strict: p.strict.is_checked(),
+        numbers: *p.numbers.borrow(),
+        groups: editors(p)
+            .iter()
+            .map(|e| Sessiongroup {
+                name: column_name(&e.label()),
+                data: e.buffer().unwrap().text(),
+            })
+            .collect(),
+        results,
+        document: p.document.borrow().clone(),
+        ..Session::default()
+    };
 
-    for cv in v {
-        if cv == pv {
-            count += 1;
-        } else {
-            values_out.push(pv);
-            counts_out.push(count);
-            pv = cv;
-            count = 1;
+    match save_session(&session, &path) {
+        Ok(()) => {
+            remember_session(&path);
+            recent_menu(menu, p);
         }
+        Err(e) => alert(368, 265, &e),
     }
-    values_out.push(pv);
-    counts_out.push(count);
-
-    return (values_out, counts_out);
 }
 
-// Calculate Percent difference
-fn per_change(f: &f64, s: &f64) -> f64 {
-    (s - f) / f.abs() * 100.0
-}
+// Restore a saved session, its seed stays with the results and Edit/Random Seed offers it to repeat them
+fn open_session(p: &Parameters, path: &std::path::Path, menu: &mut MenuBar) {
+    let session = match load_session(path) {
+        Ok(s) => s,
+        Err(e) => {
+            alert(368, 265, &e);
+            return;
+        }
+    };
+    let mut p = p.clone();
+    let settings = Settings {
+        seed: None,
+        ..session.settings.clone()
+    };
+    set_form(&p, &settings, session.strict, session.numbers);
 
-// Comparison function for vec<64> sorting
-fn cmp_f64(a: &f64, b: &f64) -> Ordering {
-    if a.is_nan() {
-        return Ordering::Greater;
+    // Match the number of data columns to the saved groups
+    while editors(&p).len() < session.groups.len() {
+        add_group(&mut p);
     }
-    if b.is_nan() {
-        return Ordering::Less;
+    while editors(&p).len() > session.groups.len().max(2) {
+        remove_group(&mut p);
     }
-    if a < b {
-        return Ordering::Less;
-    } else if a > b {
-        return Ordering::Greater;
+    for (i, mut e) in editors(&p).into_iter().enumerate() {
+        let g = session.groups.get(i).cloned().unwrap_or_default();
+        if g.name.is_empty() {
+            e.set_label(&format!("Data {}", group_name(i)));
+        } else {
+            e.set_label(&format!("Data {} ({})", group_name(i), g.name));
+        }
+        e.buffer().unwrap().set_text(&g.data);
     }
-    Ordering::Equal
+    p.columns.redraw();
+
+    // The charts are not saved, Calculate draws them again
+    p.output.buffer().unwrap().set_text(&session.results);
+    *p.document.borrow_mut() = session.document.clone();
+    *p.last.borrow_mut() = session.settings.clone();
+    p.charts.borrow_mut().clear();
+    p.plot_choice.clear();
+    p.plot_area.redraw();
+
+    // The list is rebuilt after the menu callback returns, it may have been picked from it
+    remember_session(path);
+    let mut menu = menu.clone();
+    add_timeout3(0.0, move |_| recent_menu(&mut menu, &p));
 }
 
-// Calculate SD of a sample
-fn sd_sample(x: &[f64], mean: &f64) -> f64 {
-    let mut sd: f64 = 0.0;
-
-    for v in x.iter() {
-        sd += (v - mean).powf(2.0);
-    }
-    (sd / (x.len() - 1) as f64).sqrt()
+// Put settings into the form, a fixed seed included
+fn set_form(p: &Parameters, s: &Settings, strict: bool, numbers: Numberformat) {
+    let mut p = p.clone();
+
+    p.paired_data.set_checked(s.paired);
+    p.two_tailed.toggle(s.two_tailed);
+    p.one_tailed.toggle(!s.two_tailed);
+    p.cinterval.set_value(&s.confidence.to_string());
+    p.iterations.set_value(&(s.iterations / 1000).to_string());
+    p.zthresh.set_value(&format!("{:?}", s.zthresh));
+    p.adjust.set_value(match s.adjust {
+        Adjust::None => 0,
+        Adjust::Bonferroni => 1,
+        Adjust::Holm => 2,
+        Adjust::BenjaminiHochberg => 3,
+    });
+    p.missing.set_value(match s.missing {
+        Missing::Listwise => 0,
+        Missing::Pairwise => 1,
+    });
+    p.strict.set_checked(strict);
+    *p.numbers.borrow_mut() = numbers;
+    *p.seed.borrow_mut() = s.seed.map(|v| v.to_string()).unwrap_or_default();
 }
 
-// Calculate SD of a sample
-fn sd_pop(x: &[f64], mean: &f64) -> f64 {
-    let mut sd: f64 = 0.0;
-
-    for v in x.iter() {
-        sd += (v - mean).powf(2.0);
+// Keep the form and window size as the defaults for the next launch, a bad form value keeps the old ones
+fn save_defaults(p: &Parameters, w: &Window) {
+    if let Ok(settings) = form_settings(p) {
+        let prefs = Prefs {
+            settings: Settings {
+                seed: None,
+                ..settings
+            },
+            strict: p.strict.is_checked(),
+            numbers: *p.numbers.borrow(),
+            width: w.w(),
+            height: w.h(),
+        };
+        save_prefs(&prefs).ok();
     }
-    (sd / x.len() as f64).sqrt()
 }
 
-// Calculate Skewness
-fn skewness(vec: &[f64], mean: &f64, sd: &f64) -> f64 {
-    let sz: f64 = vec.len() as f64;
-    let mut tmpsum: f64 = 0.0;
-    let sdp = sd.powf(3.0);
+// Fill the preset dropdown from the saved presets and select one by name
+fn preset_choices(p: &Parameters, select: &str) {
+    let mut choice = p.preset.clone();
+    choice.clear();
 
-    for v in &mut vec.iter() {
-        tmpsum += (v - mean).powf(3.0) / sdp;
+    for (i, preset) in presets().iter().enumerate() {
+        choice.add_choice(&menu_label(&preset.name));
+        if preset.name.eq_ignore_ascii_case(select) {
+            choice.set_value(i as i32);
+        }
     }
-
-    (sz / ((sz - 1.0) * (sz - 2.0))) * tmpsum
+    choice.redraw();
 }
 
-// Calculate Kurtosis
-fn kurtosis(vec: &[f64], mean: &f64, sd: &f64) -> f64 {
-    let sz: f64 = vec.len() as f64;
-    let mut tmpsum: f64 = 0.0;
-    let sdp = sd.powf(4.0);
-
-    for v in &mut vec.iter() {
-        tmpsum += (v - mean).powf(4.0) / sdp;
+// The preset picked in the dropdown, the list is in the same order as presets()
+fn selected_preset(p: &Parameters) -> std::option::Option<Preset> {
+    let i = p.preset.value();
+    if i < 0 {
+        return None;
     }
-
-    (((sz * (sz + 1.0)) / ((sz - 1.0) * (sz - 2.0) * (sz - 3.0))) * tmpsum)
-        - ((3.0 * (sz - 1.0) * (sz - 1.0)) / ((sz - 2.0) * (sz - 3.0)))
+    presets().into_iter().nth(i as usize)
 }
 
-// Rankify
-fn rankify(x: &[f64]) -> Vec<f64> {
-    let n = x.len();
-    let mut rank: Vec<f64> = Vec::new();
-
-    for i in 0..n {
-        let mut r = 1;
-        let mut s = 1;
-
-        for j in 0..i {
-            if x[j] < x[i] {
-                r += 1;
-            }
-            if (x[j] - x[i]).abs() < f64::EPSILON {
-                s += 1;
-            }
-        }
-
-        for j in (i + 1)..n {
-            if x[j] < x[i] {
-                r += 1;
-            }
-            if (x[j] - x[i]).abs() < f64::EPSILON {
-                s += 1;
-            }
+// Save the form as a named preset, offering the selected name so it can be updated
+fn save_preset_dialog(p: &Parameters) {
+    let settings = match form_settings(p) {
+        Ok(s) => s,
+        Err(e) => {
+            alert(368, 265, &e);
+            return;
         }
+    };
+    let current = selected_preset(p).map(|s| s.name).unwrap_or_default();
+    let Some(name) = input_default("Preset name", &current) else {
+        return;
+    };
 
-        rank.push(r as f64 + (s as f64 - 1.0) * 0.5);
+    let preset = Preset {
+        name: name.trim().to_string(),
+        settings,
+        strict: p.strict.is_checked(),
+        numbers: *p.numbers.borrow(),
+    };
+    match save_preset(&preset) {
+        Ok(()) => preset_choices(p, &preset.name),
+        Err(e) => alert(368, 265, &e),
     }
-    rank
 }
 
-// Calculate R Correlation
-fn r_value(x: Vec<f64>, y: Vec<f64>) -> f64 {
-    let mut xmx_sum: f64 = 0.0;
-    let mut ymy_sum: f64 = 0.0;
-    let mut xmx_ymy_sum: f64 = 0.0;
-
-    let mx = mean(&x);
-    let my = mean(&y);
+// Add a preset shared as a file to the saved ones and use it
+fn import_preset(p: &Parameters) {
+    let mut dialog = NativeFileChooser::new(NativeFileChooserType::BrowseFile);
+    dialog.set_filter("Presets\t*.toml");
+    dialog.show();
 
-    for i in 0..x.len() {
-        xmx_sum += (x[i] - mx) * (x[i] - mx);
-        ymy_sum += (y[i] - my) * (y[i] - my);
-        xmx_ymy_sum += (x[i] - mx) * (y[i] - my);
-    }
-
-    xmx_ymy_sum / (xmx_sum * ymy_sum).sqrt()
-}
-
-// Calculate R^2
-fn r2_value(x: &Vec<f64>, y: &Vec<f64>) -> f64 {
-    let mut xy_sum: f64 = 0.0;
-    let mut x_sum: f64 = 0.0;
-    let mut y_sum: f64 = 0.0;
-    let mut x2_sum: f64 = 0.0;
-    let mut y2_sum: f64 = 0.0;
-    let n = x.len() as f64;
-
-    for i in 0..x.len() {
-        xy_sum += x[i] * y[i];
-        x_sum += x[i];
-        y_sum += y[i];
-        x2_sum += x[i] * x[i];
-        y2_sum += y[i] * y[i];
+    let path = dialog.filename();
+    if path.as_os_str().is_empty() {
+        return;
     }
 
-    let r: f64 = (n * xy_sum - x_sum * y_sum)
-        / ((n * x2_sum - x_sum * x_sum) * (n * y2_sum - y_sum * y_sum)).sqrt();
-    r * r
-}
-
-// Calculate Log Gamma
-fn l_gamma(x: f64) -> f64 {
-    let coef: [f64; 6] = [
-        76.18009172947146,
-        -86.50532032941678,
-        24.01409824083091,
-        -1.231739572450155,
-        1.208650973866179E-3,
-        -0.5395239384953E-5,
-    ];
-    let logsqrttwopi: f64 = 0.9189385332046728;
-    let y: f64 = x + 5.5;
-    let mut denom: f64 = x + 1.0;
-    let mut series: f64 = 1.000000000190015;
-
-    for v in &coef {
-        series += v / denom;
-        denom += 1.0;
+    match read_preset(&path).and_then(|preset| save_preset(&preset).map(|_| preset)) {
+        Ok(preset) => {
+            preset_choices(p, &preset.name);
+            set_form(p, &preset.settings, preset.strict, preset.numbers);
+        }
+        Err(e) => alert(368, 265, &e),
     }
-
-    logsqrttwopi + (x + 0.5) * y.ln() - y + (series / x).ln()
 }
 
-// Calculate a p value from F and A count, B count
-fn p_from_f(f: f64, df1: usize, df2: usize) -> f64 {
-    1.0 - incomplete_beta(
-        df1 as f64 * f / (df1 as f64 * f + df2 as f64),
-        0.5 * df1 as f64,
-        0.5 * df2 as f64,
-    )
-}
+// Write the selected preset to a file to share it
+fn export_preset(p: &Parameters) {
+    let Some(preset) = selected_preset(p) else {
+        alert(368, 265, "Pick a preset to export");
+        return;
+    };
 
-// Calculate incomplete Beta
-fn incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
-    if (x - 0.0).abs() < f64::EPSILON {
-        return 0.0;
-    }
+    let mut dialog = NativeFileChooser::new(NativeFileChooserType::BrowseSaveFile);
+    dialog.set_option(NativeFileChooserOptions::SaveAsConfirm);
+    dialog.set_filter("Presets\t*.toml");
+    dialog.set_preset_file(&format!("{}.toml", preset.name));
+    dialog.show();
 
-    if (x - 1.0).abs() < f64::EPSILON {
-        return 1.0;
+    let mut path = dialog.filename();
+    if path.as_os_str().is_empty() {
+        return;
     }
-
-    let l_beta: f64 = l_gamma(a + b) - l_gamma(a) - l_gamma(b) + a * x.ln() + b * (1.0 - x).ln();
-
-    if x < (a + 1.0) / (a + b + 2.0) {
-        l_beta.exp() * contfrac_beta(x, a, b) / a
-    } else {
-        1.0 - l_beta.exp() * contfrac_beta(1.0 - x, b, a) / b
+    if path.extension().is_none() {
+        path.set_extension("toml");
     }
-}
 
-fn contfrac_beta(x: f64, a: f64, b: f64) -> f64 {
-    let itmax: usize = 200;
-    let eps: f64 = 3.0e-7;
-
-    let mut bm = 1.0;
-    let mut az = 1.0;
-    let mut am = 1.0;
-    let qab = a + b;
-    let qap = a + 1.0;
-    let qam = a - 1.0;
-    let mut bz = 1.0 - qab * x / qap;
-    let mut em: f64;
-    let mut tem: f64;
-    let mut d: f64;
-    let mut ap: f64;
-    let mut bp: f64;
-    let mut app: f64;
-    let mut bpp: f64;
-    let mut aold: f64;
-
-    for i in 0..itmax {
-        em = i as f64 + 1.0;
-        tem = em + em;
-        d = em * (b - em) * x / ((qam + tem) * (a + tem));
-        ap = az + d * am;
-        bp = bz + d * bm;
-        d = -(a + em) * (qab + em) * x / ((qap + tem) * (a + tem));
-        app = ap + d * az;
-        bpp = bp + d * bz;
-        aold = az;
-        am = ap / bpp;
-        bm = bp / bpp;
-        az = app / bpp;
-        bz = 1.0;
-        if (az - aold).abs() < eps * az.abs() {
-            return az;
-        }
+    if let Err(e) = write_preset(&preset, &path) {
+        alert(368, 265, &e);
     }
-
-    0.0
 }
 
-// Calculate P Value from T statistic
-fn p_from_t(ws: f64, dof: f64) -> f64 {
-    let a: f64 = dof / 2.0;
-    let mut value = dof / (ws * ws + dof);
-
-    if value.is_infinite() || value.is_nan() {
-        return 1.0;
+// Fill File/Open Recent with the recent sessions
+fn recent_menu(menu: &mut MenuBar, p: &Parameters) {
+    let idx = menu.find_index("&File/Open &Recent");
+    if idx < 0 {
+        return;
     }
-
-    let beta = l_gamma(a) + 0.5723649429247001 - l_gamma(a + 0.5);
-    let acu = 0.1E-14;
-    let mut ai: f64;
-    let mut cx: f64;
-    let mut ns: i32;
-    let mut psq: f64;
-    let mut rx: f64;
-    let mut temp: f64;
-    let mut term: f64;
-    let xx: f64;
-    let qq: f64;
-    let pp: f64;
-    let indx: i32;
-
-    if !(0.0..=1.0).contains(&value) {
-        return value;
+    menu.clear_submenu(idx).ok();
+
+    let recent = recent_sessions();
+    if recent.is_empty() {
+        menu.add(
+            "&File/Open &Recent/No Recent Sessions",
+            Shortcut::None,
+            MenuFlag::Inactive,
+            |_| {},
+        );
     }
-
-    if (value - 0.0).abs() < f64::EPSILON || (value - 1.0).abs() < f64::EPSILON {
-        return value;
+    for path in recent {
+        let p = p.clone();
+        menu.add(
+            &format!(
+                "&File/Open &Recent/{}",
+                menu_label(&path.display().to_string())
+            ),
+            Shortcut::None,
+            MenuFlag::Normal,
+            move |m| open_session(&p, &path, m),
+        );
     }
+}
 
-    psq = a + 0.5;
-    cx = 1.0 - value;
-
-    if a < psq * value {
-        xx = cx;
-        cx = value;
-        pp = 0.5;
-        qq = a;
-        indx = 1;
-    } else {
-        xx = value;
-        pp = a;
-        qq = 0.5;
-        indx = 0;
+// Save the last results as a Markdown, HTML, PDF or LaTeX report, HTML and PDF include the charts
+// The first format is the default when the file name has no extension
+fn export_report(p: &Parameters, formats: &[(&str, &str)]) {
+    let document = p.document.borrow();
+    if document.sections.is_empty() {
+        alert(368, 265, "Calculate before exporting a report");
+        return;
     }
 
-    term = 1.0;
-    ai = 1.0;
-    value = 1.0;
-    ns = (qq + cx * psq) as i32;
-    rx = xx / cx;
-    temp = qq - ai;
+    let mut dialog = NativeFileChooser::new(NativeFileChooserType::BrowseSaveFile);
+    dialog.set_option(NativeFileChooserOptions::SaveAsConfirm);
+    let filter: Vec<String> = formats
+        .iter()
+        .map(|(name, ext)| format!("{}\t*.{}", name, ext))
+        .collect();
+    dialog.set_filter(&filter.join("\n"));
+    dialog.set_preset_file(&format!("report.{}", formats[0].1));
+    dialog.show();
 
-    if ns == 0 {
-        rx = xx;
+    let mut path = dialog.filename();
+    if path.as_os_str().is_empty() {
+        return;
     }
-
-    loop {
-        term = term * temp * rx / (pp + ai);
-        value += term;
-        temp = term.abs();
-
-        if temp <= acu && temp <= acu * value {
-            value = value * (pp * xx.ln() + (qq - 1.0) * cx.ln() - beta).exp() / pp;
-
-            if indx != 0 {
-                value = 1.0 - value;
-            }
-
-            break;
-        }
-
-        ai += 1.0;
-        ns -= 1;
-
-        if 0 <= ns {
-            temp = qq - ai;
-
-            if ns == 0 {
-                rx = xx;
-            }
-        } else {
-            temp = psq;
-            psq += 1.0;
-        }
+    if path.extension().is_none() {
+        let picked = formats.get(dialog.filter_value().max(0) as usize);
+        path.set_extension(picked.unwrap_or(&formats[0]).1);
     }
 
-    value
-}
-
-// Calculate P-Value from CI
-fn p_from_ci(l: f64, u: f64, m: f64, cl: f64) -> f64 {
-    let s: f64 = erf_inv(cl) * f64::consts::SQRT_2;
-    let se: f64 = (u - l) / (2.0 * s);
-    let z: f64 = m / se;
-
-    (1.0 - p_from_z(z.abs())) * 2.0
-}
-
-// Calculate inverse ERF
-fn erf_inv(x: f64) -> f64 {
-    let mut w: f64;
-    let mut p: f64;
-
-    w = -((1.0 - x) * (1.0 + x)).ln();
-
-    if w < 5.000000 {
-        w -= 2.500000;
-        p = 2.81022636e-08;
-        p = 3.43273939e-07 + p * w;
-        p = -3.5233877e-06 + p * w;
-        p = -4.39150654e-06 + p * w;
-        p = 0.00021858087 + p * w;
-        p = -0.00125372503 + p * w;
-        p = -0.00417768164 + p * w;
-        p = 0.246640727 + p * w;
-        p = 1.50140941 + p * w;
-    } else {
-        w = w.sqrt() - 3.000000;
-        p = -0.000200214257;
-        p = 0.000100950558 + p * w;
-        p = 0.00134934322 + p * w;
-        p = -0.00367342844 + p * w;
-        p = 0.00573950773 + p * w;
-        p = -0.0076224613 + p * w;
-        p = 0.00943887047 + p * w;
-        p = 1.00167406 + p * w;
-        p = 2.83297682 + p * w;
+    if let Err(e) = save_report(&document, &p.charts.borrow(), &path) {
+        alert(368, 265, &e);
     }
-
-    p * x
 }
 
-// Calculate Z from Confidence Level
-fn _z_from_cl(cl: f64) -> f64 {
-    erf_inv(cl) * f64::consts::SQRT_2
-}
-
-fn zcount(x: &Vec<f64>, zth: f64) -> Zscoreresults {
-    let mean = mean(&x);
-    let sd = sd_pop(&x, &mean);
-    let mut zresults: Zscoreresults = Zscoreresults {
-        pluscount: 0,
-        minuscount: 0,
-        pluspercent: 0.0,
-        minuspercent: 0.0,
-    };
-
-    for v in x {
-        let z = (v - mean) / sd;
-
-        if z >= zth.abs() {
-            zresults.pluscount += 1;
-        }
-        if z <= -zth.abs() {
-            zresults.minuscount += 1;
+impl Canvas for Fltkcanvas {
+    fn line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, color: Rgb, dashed: bool) {
+        fltk::draw::set_draw_rgb_color(color.0, color.1, color.2);
+        if dashed {
+            fltk::draw::set_line_style(LineStyle::Dash, 1);
         }
+        fltk::draw::draw_line(
+            (self.x + x1) as i32,
+            (self.y + y1) as i32,
+            (self.x + x2) as i32,
+            (self.y + y2) as i32,
+        );
+        fltk::draw::set_line_style(LineStyle::Solid, 0);
     }
 
-    zresults.pluspercent = (zresults.pluscount as f64 / x.len() as f64) * 100.0;
-    zresults.minuspercent = (zresults.minuscount as f64 / x.len() as f64) * 100.0;
-
-    zresults
-}
-
-// Calculate P from Z
-fn p_from_z(z: f64) -> f64 {
-    let mut y: f64;
-    let x: f64;
-    let w: f64;
-
-    if z == 0.0 {
-        x = 0.0;
-    } else {
-        y = 0.5 * z.abs();
-
-        if y >= 3.0 {
-            x = 1.0;
-        } else if y < 1.0 {
-            w = y * y;
-            x = ((((((((0.000124818987 * w - 0.001075204047) * w + 0.005198775019) * w
-                - 0.019198292004)
-                * w
-                + 0.059054035642)
-                * w
-                - 0.151968751364)
-                * w
-                + 0.319152932694)
-                * w
-                - 0.531923007300)
-                * w
-                + 0.797884560593)
-                * y
-                * 2.0;
+    fn rect(&mut self, x: f64, y: f64, w: f64, h: f64, color: Rgb, fill: bool) {
+        fltk::draw::set_draw_rgb_color(color.0, color.1, color.2);
+        if fill {
+            fltk::draw::draw_rectf((self.x + x) as i32, (self.y + y) as i32, w as i32, h as i32);
         } else {
-            y -= 2.0;
-            x = (((((((((((((-0.000045255659 * y + 0.000152529290) * y - 0.000019538132)
-                * y
-                - 0.000676904986)
-                * y
-                + 0.001390604284)
-                * y
-                - 0.000794620820)
-                * y
-                - 0.002034254874)
-                * y
-                + 0.006549791214)
-                * y
-                - 0.010557625006)
-                * y
-                + 0.011630447319)
-                * y
-                - 0.009279453341)
-                * y
-                + 0.005353579108)
-                * y
-                - 0.002141268741)
-                * y
-                + 0.000535310849)
-                * y
-                + 0.999936657524;
+            fltk::draw::draw_rect((self.x + x) as i32, (self.y + y) as i32, w as i32, h as i32);
         }
     }
 
-    if z > 0.0 {
-        (x + 1.0) * 0.5
-    } else {
-        (1.0 - x) * 0.5
+    fn circle(&mut self, x: f64, y: f64, r: f64, color: Rgb) {
+        fltk::draw::set_draw_rgb_color(color.0, color.1, color.2);
+        fltk::draw::draw_circle(self.x + x, self.y + y, r);
     }
-}
 
-// Pretty Format Scientific Numbers
-fn science_pretty_format(value: f64, digits: usize) -> String {
-    if value.abs() == 0.0 {
-        return "0".to_string();
+    fn text(&mut self, x: f64, y: f64, s: &str, anchor: Anchor, color: Rgb) {
+        fltk::draw::set_draw_rgb_color(color.0, color.1, color.2);
+        fltk::draw::set_font(fltk::enums::Font::Helvetica, 12);
+        let w = fltk::draw::width(s);
+        let x = match anchor {
+            Anchor::Start => x,
+            Anchor::Middle => x - w / 2.0,
+            Anchor::End => x - w,
+        };
+        fltk::draw::draw_text(s, (self.x + x) as i32, (self.y + y) as i32);
     }
-    if value.abs() >= 10000.0 || value.abs() < 0.001 {
-        return format!("{:.*e}", digits, value);
+
+    fn vtext(&mut self, x: f64, y: f64, s: &str, color: Rgb) {
+        fltk::draw::set_draw_rgb_color(color.0, color.1, color.2);
+        fltk::draw::set_font(fltk::enums::Font::Helvetica, 12);
+        let w = fltk::draw::width(s);
+        fltk::draw::draw_text_angled(90, s, (self.x + x) as i32, (self.y + y + w / 2.0) as i32);
     }
-    format!("{:.*}", digits, value)
-        .trim_end_matches(|c| c == '0')
-        .trim_end_matches(|c| c == '.')
-        .to_string()
 }
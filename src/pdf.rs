@@ -0,0 +1,427 @@
+use crate::document::*;
+use crate::plot::*;
+
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+
+// A4 portrait in points, with the margin kept clear on every side
+const PAGE_W: f32 = 595.0;
+const PAGE_H: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+
+// Charts are laid out at the plot window size then scaled to the text width
+const CHART_W: f64 = 640.0;
+const CHART_H: f64 = 480.0;
+
+const REGULAR: Name = Name(b"F1");
+const BOLD: Name = Name(b"F2");
+
+// Helvetica advance widths for space to tilde, in thousandths of the font size
+const HELVETICA: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+// Pages written so far, y is the top of the free space on the current page
+struct Pages {
+    done: Vec<Content>,
+    current: Content,
+    y: f32,
+}
+
+// Canvas drawing a chart into a page, the chart is flipped and scaled into place
+struct Pdfcanvas<'a> {
+    content: &'a mut Content,
+}
+
+// Render a report with its charts as a paginated PDF
+pub fn pdf(doc: &Document, charts: &[Chart]) -> Vec<u8> {
+    let mut pages = Pages {
+        done: Vec::new(),
+        current: Content::new(),
+        y: PAGE_H - MARGIN,
+    };
+    let width = PAGE_W - 2.0 * MARGIN;
+
+    pages.text_line(&doc.title, BOLD, 16.0, 24.0);
+    pages.y -= 6.0;
+
+    if !doc.summary.is_empty() {
+        pages.text_line("Summary", BOLD, 12.0, 20.0);
+        for line in &doc.summary {
+            for l in wrap(line, 9.0, width) {
+                pages.text_line(&l, REGULAR, 9.0, 12.0);
+            }
+        }
+        pages.y -= 8.0;
+    }
+
+    for sec in &doc.sections {
+        pages.grid(&sec.title, &sec.grid, width);
+    }
+
+    // One chart per half page
+    let scale = width as f64 / CHART_W;
+    let chart_h = (CHART_H * scale) as f32;
+    if !charts.is_empty() {
+        pages.need(chart_h + 24.0);
+        pages.text_line("Plots", BOLD, 12.0, 20.0);
+    }
+    for chart in charts {
+        pages.need(chart_h + 12.0);
+        let top = pages.y;
+        let c = &mut pages.current;
+        c.save_state();
+        c.transform([scale as f32, 0.0, 0.0, -(scale as f32), MARGIN, top]);
+        draw(chart, &mut Pdfcanvas { content: c }, CHART_W, CHART_H);
+        c.restore_state();
+        pages.y -= chart_h + 12.0;
+    }
+
+    pages.finish(&doc.title)
+}
+
+impl Pages {
+    // Start a new page unless there is room for the height asked for
+    fn need(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            let page = std::mem::replace(&mut self.current, Content::new());
+            self.done.push(page);
+            self.y = PAGE_H - MARGIN;
+        }
+    }
+
+    fn text_line(&mut self, s: &str, font: Name, size: f32, leading: f32) {
+        self.need(leading);
+        self.y -= leading;
+        text_at(
+            &mut self.current,
+            MARGIN,
+            self.y + leading * 0.25,
+            s,
+            font,
+            size,
+        );
+    }
+
+    // A titled table, the header is repeated on each page the rows run onto
+    fn grid(&mut self, title: &str, g: &Grid, width: f32) {
+        let cols = g.header.len().max(1);
+        let pad = 6.0;
+
+        // Columns take their widest cell, shrinking the font if the table is too wide
+        let mut natural = vec![0.0f32; cols];
+        for r in std::iter::once(&g.header).chain(&g.rows) {
+            for (i, c) in r.iter().enumerate().take(cols) {
+                natural[i] = natural[i].max(text_width(c, 9.0) + 2.0 * pad);
+            }
+        }
+        let total: f32 = natural.iter().sum();
+        let fit = if total > width { width / total } else { 1.0 };
+        let size = (9.0 * fit).max(5.0);
+        let widths: Vec<f32> = natural.iter().map(|w| w * fit).collect();
+        let row_h = size * 1.7;
+
+        self.need(20.0 + 2.0 * row_h);
+        self.text_line(title, BOLD, 12.0, 20.0);
+        self.header_row(&g.header, &widths, size, row_h, pad);
+
+        for (n, r) in g.rows.iter().enumerate() {
+            if self.y - row_h < MARGIN {
+                self.need(row_h * 2.0);
+                self.header_row(&g.header, &widths, size, row_h, pad);
+            }
+            // Light banding keeps long rows readable
+            if n % 2 == 1 {
+                let c = &mut self.current;
+                c.set_fill_rgb(0.96, 0.96, 0.96);
+                c.rect(MARGIN, self.y - row_h, widths.iter().sum(), row_h);
+                c.fill_nonzero();
+            }
+            self.cells(r, &widths, REGULAR, size, row_h, pad);
+        }
+        self.y -= 12.0;
+    }
+
+    fn header_row(&mut self, header: &[String], widths: &[f32], size: f32, row_h: f32, pad: f32) {
+        self.cells(header, widths, BOLD, size, row_h, pad);
+
+        let c = &mut self.current;
+        c.set_stroke_rgb(0.0, 0.0, 0.0);
+        c.set_line_width(0.5);
+        c.move_to(MARGIN, self.y);
+        c.line_to(MARGIN + widths.iter().sum::<f32>(), self.y);
+        c.stroke();
+    }
+
+    // One row of cells, the first column reads left to right and the numbers line up on the right
+    fn cells(&mut self, r: &[String], widths: &[f32], font: Name, size: f32, row_h: f32, pad: f32) {
+        self.y -= row_h;
+        let base = self.y + (row_h - size) / 2.0 + size * 0.2;
+        let mut x = MARGIN;
+
+        for (i, w) in widths.iter().enumerate() {
+            let s = r.get(i).map_or("", |s| s.as_str());
+            let tx = if i == 0 {
+                x + pad
+            } else {
+                x + w - pad - text_width(s, size)
+            };
+            text_at(&mut self.current, tx, base, s, font, size);
+            x += w;
+        }
+    }
+
+    // Number the pages and write the document
+    fn finish(mut self, title: &str) -> Vec<u8> {
+        self.done.push(self.current);
+        let count = self.done.len();
+
+        let catalog = Ref::new(1);
+        let tree = Ref::new(2);
+        let regular = Ref::new(3);
+        let bold = Ref::new(4);
+        let info = Ref::new(5);
+        let mut next = 6;
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog).pages(tree);
+        pdf.document_info(info)
+            .title(TextStr(title))
+            .creator(TextStr("Bootstrap Statistics Calculator"));
+
+        let mut kids = Vec::new();
+        for (i, mut content) in self.done.into_iter().enumerate() {
+            let footer = format!("Page {} of {}", i + 1, count);
+            let w = text_width(&footer, 8.0);
+            text_at(
+                &mut content,
+                PAGE_W - MARGIN - w,
+                MARGIN / 2.0,
+                &footer,
+                REGULAR,
+                8.0,
+            );
+
+            let (page, stream) = (Ref::new(next), Ref::new(next + 1));
+            next += 2;
+            kids.push(page);
+
+            let mut p = pdf.page(page);
+            p.media_box(Rect::new(0.0, 0.0, PAGE_W, PAGE_H));
+            p.parent(tree);
+            p.contents(stream);
+            p.resources()
+                .fonts()
+                .pair(REGULAR, regular)
+                .pair(BOLD, bold);
+            p.finish();
+
+            pdf.stream(stream, &content.finish());
+        }
+        pdf.pages(tree).kids(kids).count(count as i32);
+
+        for (id, name) in [(regular, "Helvetica"), (bold, "Helvetica-Bold")] {
+            pdf.type1_font(id)
+                .base_font(Name(name.as_bytes()))
+                .encoding_predefined(Name(b"WinAnsiEncoding"));
+        }
+
+        pdf.finish()
+    }
+}
+
+fn text_at(c: &mut Content, x: f32, y: f32, s: &str, font: Name, size: f32) {
+    c.set_fill_rgb(0.0, 0.0, 0.0);
+    c.begin_text();
+    c.set_font(font, size);
+    c.next_line(x, y);
+    c.show(Str(&win_ansi(s)));
+    c.end_text();
+}
+
+// Width of a line of Helvetica, characters outside ASCII count as a digit
+fn text_width(s: &str, size: f32) -> f32 {
+    let units: u32 = win_ansi(s)
+        .iter()
+        .map(|b| match b {
+            32..=126 => HELVETICA[(*b - 32) as usize] as u32,
+            _ => 556,
+        })
+        .sum();
+    units as f32 * size / 1000.0
+}
+
+// Break text into lines that fit a width, at spaces
+fn wrap(s: &str, size: f32, width: f32) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut line = String::new();
+
+    for word in s.split(' ') {
+        let next = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", line, word)
+        };
+        if text_width(&next, size) > width && !line.is_empty() {
+            out.push(std::mem::replace(&mut line, word.to_string()));
+        } else {
+            line = next;
+        }
+    }
+    out.push(line);
+
+    out
+}
+
+// The base fonts only carry the Windows Latin 1 characters, spell out the rest
+fn win_ansi(s: &str) -> Vec<u8> {
+    let mut out = Vec::new();
+
+    for ch in s.replace("τb", "tau-b").chars() {
+        match ch {
+            ' '..='~' => out.push(ch as u8),
+            '\u{a0}'..='\u{ff}' => out.push(ch as u32 as u8),
+            '−' => out.push(b'-'),
+            '–' => out.push(0x96),
+            '—' => out.push(0x97),
+            '…' => out.push(0x85),
+            '•' => out.push(0x95),
+            '’' => out.push(0x92),
+            '≈' => out.push(b'~'),
+            'ρ' => out.extend_from_slice(b"rho"),
+            'τ' => out.extend_from_slice(b"tau"),
+            _ => out.push(b'?'),
+        }
+    }
+    out
+}
+
+fn rgb(c: Rgb) -> (f32, f32, f32) {
+    (c.0 as f32 / 255.0, c.1 as f32 / 255.0, c.2 as f32 / 255.0)
+}
+
+impl Canvas for Pdfcanvas<'_> {
+    fn line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, color: Rgb, dashed: bool) {
+        let (r, g, b) = rgb(color);
+        let c = &mut *self.content;
+        c.set_stroke_rgb(r, g, b);
+        c.set_line_width(1.0);
+        if dashed {
+            c.set_dash_pattern([4.0, 3.0], 0.0);
+        }
+        c.move_to(x1 as f32, y1 as f32);
+        c.line_to(x2 as f32, y2 as f32);
+        c.stroke();
+        if dashed {
+            c.set_dash_pattern([], 0.0);
+        }
+    }
+
+    fn rect(&mut self, x: f64, y: f64, w: f64, h: f64, color: Rgb, fill: bool) {
+        let (r, g, b) = rgb(color);
+        let c = &mut *self.content;
+        c.rect(x as f32, y as f32, w as f32, h as f32);
+        if fill {
+            c.set_fill_rgb(r, g, b);
+            c.fill_nonzero();
+        } else {
+            c.set_stroke_rgb(r, g, b);
+            c.set_line_width(1.0);
+            c.stroke();
+        }
+    }
+
+    fn circle(&mut self, x: f64, y: f64, r: f64, color: Rgb) {
+        let (cr, cg, cb) = rgb(color);
+        let (x, y, r) = (x as f32, y as f32, r as f32);
+        // Four Bezier quarter arcs
+        let k = 0.5523 * r;
+        let c = &mut *self.content;
+        c.set_stroke_rgb(cr, cg, cb);
+        c.set_line_width(1.0);
+        c.move_to(x + r, y);
+        c.cubic_to(x + r, y + k, x + k, y + r, x, y + r);
+        c.cubic_to(x - k, y + r, x - r, y + k, x - r, y);
+        c.cubic_to(x - r, y - k, x - k, y - r, x, y - r);
+        c.cubic_to(x + k, y - r, x + r, y - k, x + r, y);
+        c.close_and_stroke();
+    }
+
+    fn text(&mut self, x: f64, y: f64, s: &str, anchor: Anchor, color: Rgb) {
+        let w = text_width(s, 12.0) as f64;
+        let x = match anchor {
+            Anchor::Start => x,
+            Anchor::Middle => x - w / 2.0,
+            Anchor::End => x - w,
+        };
+        // The page is flipped to chart coordinates so the text matrix flips the glyphs back
+        self.glyphs([1.0, 0.0, 0.0, -1.0, x as f32, y as f32], s, color);
+    }
+
+    fn vtext(&mut self, x: f64, y: f64, s: &str, color: Rgb) {
+        let w = text_width(s, 12.0) as f64;
+        self.glyphs(
+            [0.0, -1.0, -1.0, 0.0, x as f32, (y + w / 2.0) as f32],
+            s,
+            color,
+        );
+    }
+}
+
+impl Pdfcanvas<'_> {
+    fn glyphs(&mut self, matrix: [f32; 6], s: &str, color: Rgb) {
+        let (r, g, b) = rgb(color);
+        let c = &mut *self.content;
+        c.set_fill_rgb(r, g, b);
+        c.begin_text();
+        c.set_font(REGULAR, 12.0);
+        c.set_text_matrix(matrix);
+        c.show(Str(&win_ansi(s)));
+        c.end_text();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_tables_run_onto_numbered_pages() {
+        let grid = Grid {
+            header: vec!["Value".to_string(), "Count".to_string()],
+            rows: (0..120)
+                .map(|i| vec![i.to_string(), "1".to_string()])
+                .collect(),
+        };
+        let doc = Document {
+            title: "T".to_string(),
+            summary: vec!["word ".repeat(200)],
+            sections: vec![Section {
+                title: "Unique Values".to_string(),
+                grid,
+            }],
+        };
+
+        let out = String::from_utf8_lossy(&pdf(&doc, &[])).to_string();
+        assert!(out.starts_with("%PDF"));
+        // The footer numbers every page out of the total
+        let count = out.matches("/Type /Page\n").count();
+        assert!(count > 1);
+        assert!(out.contains(&format!("Page 1 of {}", count)));
+        assert!(out.contains(&format!("Page {} of {}", count, count)));
+    }
+
+    #[test]
+    fn text_fits_the_base_fonts() {
+        assert_eq!(
+            win_ansi("Kendall's τb ≈ −1"),
+            b"Kendall's tau-b ~ -1".to_vec()
+        );
+        for l in wrap(&"word ".repeat(100), 9.0, 200.0) {
+            assert!(text_width(&l, 9.0) <= 200.0);
+        }
+    }
+}
@@ -0,0 +1,831 @@
+use crate::analysis::*;
+use crate::report::science_pretty_format;
+use crate::stats::*;
+
+// Colors as red, green, blue
+pub type Rgb = (u8, u8, u8);
+
+pub const BLACK: Rgb = (0, 0, 0);
+pub const GREY: Rgb = (160, 160, 160);
+pub const BLUE: Rgb = (31, 119, 180);
+pub const ORANGE: Rgb = (255, 127, 14);
+pub const GREEN: Rgb = (44, 160, 44);
+pub const RED: Rgb = (214, 39, 40);
+pub const SHADE: Rgb = (225, 225, 235);
+pub const PALETTE: [Rgb; 6] = [BLUE, ORANGE, GREEN, RED, (148, 103, 189), (140, 86, 75)];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+// Define where text sits relative to its anchor point
+pub enum Anchor {
+    Start,
+    Middle,
+    End,
+}
+
+#[derive(Clone, Debug)]
+// Define the things that can be drawn on a chart, all in data coordinates
+pub enum Item {
+    Points {
+        x: Vec<f64>,
+        y: Vec<f64>,
+        color: Rgb,
+    },
+    Segment {
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+        color: Rgb,
+        dashed: bool,
+    },
+    HLine {
+        y: f64,
+        label: String,
+        color: Rgb,
+        dashed: bool,
+    },
+    HBand {
+        y1: f64,
+        y2: f64,
+        color: Rgb,
+    },
+    VLine {
+        x: f64,
+        label: String,
+        color: Rgb,
+        dashed: bool,
+    },
+    Bars {
+        lo: f64,
+        width: f64,
+        counts: Vec<f64>,
+        color: Rgb,
+    },
+    Polyline {
+        x: Vec<f64>,
+        y: Vec<f64>,
+        color: Rgb,
+    },
+    Box {
+        x: f64,
+        width: f64,
+        q1: f64,
+        median: f64,
+        q3: f64,
+        lo: f64,
+        hi: f64,
+        color: Rgb,
+    },
+    Legend {
+        entries: Vec<(String, Rgb)>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+// Define how box plot outliers are picked
+pub enum Fence {
+    Tukey,
+    Zscore(f64),
+}
+
+#[derive(Clone, Debug)]
+// Define a struct for one chart
+pub struct Chart {
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+    pub x_range: (f64, f64),
+    pub y_range: (f64, f64),
+    // Names placed at x = 1, 2, ... instead of numeric x ticks when set
+    pub categories: Vec<String>,
+    pub items: Vec<Item>,
+}
+
+// Drawing surface a chart can be rendered onto, in pixels from the top left
+pub trait Canvas {
+    fn line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, color: Rgb, dashed: bool);
+    fn rect(&mut self, x: f64, y: f64, w: f64, h: f64, color: Rgb, fill: bool);
+    fn circle(&mut self, x: f64, y: f64, r: f64, color: Rgb);
+    fn text(&mut self, x: f64, y: f64, s: &str, anchor: Anchor, color: Rgb);
+    fn vtext(&mut self, x: f64, y: f64, s: &str, color: Rgb);
+}
+
+// Build every chart available for a two sample comparison
+pub fn charts(c: &Comparison) -> Vec<Chart> {
+    let mut out: Vec<Chart> = Vec::new();
+
+    // Histograms share one x range so A and B can be compared by eye
+    let (x_range, bins) = shared_bins(&c.samples);
+    out.push(histogram_chart(&c.samples[0], x_range, bins, BLUE));
+    out.push(histogram_chart(&c.samples[1], x_range, bins, ORANGE));
+
+    out.push(boot_diff_chart(c));
+
+    out.push(box_chart(&c.samples, Fence::Tukey));
+    out.push(box_chart(&c.samples, Fence::Zscore(c.settings.zthresh)));
+    out.push(violin_chart(&c.samples));
+
+    for (i, g) in c.samples.iter().enumerate() {
+        out.push(qq_chart(g, PALETTE[i]));
+    }
+    out.push(ecdf_chart(&c.samples));
+
+    if let Some(ba) = &c.blandaltman {
+        out.push(bland_altman(ba));
+    }
+
+    out
+}
+
+// Build every chart available for a k sample comparison
+pub fn multi_charts(m: &Multisample) -> Vec<Chart> {
+    let (x_range, bins) = shared_bins(&m.samples);
+
+    let mut out: Vec<Chart> = m
+        .samples
+        .iter()
+        .enumerate()
+        .map(|(i, g)| histogram_chart(g, x_range, bins, PALETTE[i % PALETTE.len()]))
+        .collect();
+
+    out.push(box_chart(&m.samples, Fence::Tukey));
+    out.push(box_chart(&m.samples, Fence::Zscore(m.settings.zthresh)));
+    out.push(violin_chart(&m.samples));
+
+    for (i, g) in m.samples.iter().enumerate() {
+        out.push(qq_chart(g, PALETTE[i % PALETTE.len()]));
+    }
+    out.push(ecdf_chart(&m.samples));
+
+    out
+}
+
+// Side by side box plots, whiskers reach the furthest value inside the fence
+pub fn box_chart(samples: &[Sample], fence: Fence) -> Chart {
+    let mut items: Vec<Item> = Vec::new();
+
+    for (i, g) in samples.iter().enumerate() {
+        let color = PALETTE[i % PALETTE.len()];
+        let x = (i + 1) as f64;
+        let mut v = g.values.clone();
+        v.sort_by(cmp_f64);
+
+        let (q1, median, q3) = (quantile(&v, 0.25), quantile(&v, 0.5), quantile(&v, 0.75));
+        let (m, sd) = (mean(&v), sd_sample(&v, &mean(&v)));
+        let inside = |y: f64| match fence {
+            Fence::Tukey => y >= q1 - 1.5 * (q3 - q1) && y <= q3 + 1.5 * (q3 - q1),
+            Fence::Zscore(zthresh) => sd == 0.0 || ((y - m) / sd).abs() <= zthresh,
+        };
+
+        let (kept, outliers): (Vec<f64>, Vec<f64>) = v.iter().partition(|y| inside(**y));
+        items.push(Item::Box {
+            x,
+            width: 0.5,
+            q1,
+            median,
+            q3,
+            lo: kept.first().copied().unwrap_or(q1).min(q1),
+            hi: kept.last().copied().unwrap_or(q3).max(q3),
+            color,
+        });
+        items.push(Item::Points {
+            x: vec![x; outliers.len()],
+            y: outliers,
+            color: RED,
+        });
+    }
+
+    Chart {
+        title: match fence {
+            Fence::Tukey => "Box Plot Tukey Fence".to_string(),
+            Fence::Zscore(_) => "Box Plot Z Thresh".to_string(),
+        },
+        x_label: String::new(),
+        y_label: "Value".to_string(),
+        x_range: (0.5, samples.len() as f64 + 0.5),
+        y_range: range(samples.iter().flat_map(|g| g.values.iter().copied())),
+        categories: samples.iter().map(|g| g.name.clone()).collect(),
+        items,
+    }
+}
+
+// Violin outlines from a kernel density estimate with a slim box inside
+pub fn violin_chart(samples: &[Sample]) -> Chart {
+    let y_range = range(samples.iter().flat_map(|g| g.values.iter().copied()));
+    let mut items: Vec<Item> = Vec::new();
+
+    // Each outline is cut at its own smallest and largest value
+    let sorted: Vec<Vec<f64>> = samples
+        .iter()
+        .map(|g| {
+            let mut v = g.values.clone();
+            v.sort_by(cmp_f64);
+            v
+        })
+        .collect();
+    let grids: Vec<Vec<f64>> = sorted
+        .iter()
+        .map(|v| {
+            let (lo, hi) = (v[0], v[v.len() - 1]);
+            (0..=100)
+                .map(|i| lo + (hi - lo) * i as f64 / 100.0)
+                .collect()
+        })
+        .collect();
+    let densities: Vec<Vec<f64>> = sorted
+        .iter()
+        .zip(&grids)
+        .map(|(v, at)| kde(v, at))
+        .collect();
+    let peak = densities.iter().flatten().copied().fold(0.0, f64::max);
+
+    for (i, v) in sorted.iter().enumerate() {
+        let color = PALETTE[i % PALETTE.len()];
+        let x = (i + 1) as f64;
+        let half: Vec<f64> = densities[i].iter().map(|d| 0.45 * d / peak).collect();
+
+        // Right side up then left side down to close the outline
+        let xs = half
+            .iter()
+            .map(|w| x + w)
+            .chain(half.iter().rev().map(|w| x - w))
+            .chain([x + half[0]]);
+        let ys = grids[i]
+            .iter()
+            .chain(grids[i].iter().rev())
+            .chain([&grids[i][0]])
+            .copied();
+        items.push(Item::Polyline {
+            x: xs.collect(),
+            y: ys.collect(),
+            color,
+        });
+
+        items.push(Item::Box {
+            x,
+            width: 0.08,
+            q1: quantile(v, 0.25),
+            median: quantile(v, 0.5),
+            q3: quantile(v, 0.75),
+            lo: v[0],
+            hi: v[v.len() - 1],
+            color: BLACK,
+        });
+    }
+
+    Chart {
+        title: "Violin Plot".to_string(),
+        x_label: String::new(),
+        y_label: "Value".to_string(),
+        x_range: (0.5, samples.len() as f64 + 0.5),
+        y_range,
+        categories: samples.iter().map(|g| g.name.clone()).collect(),
+        items,
+    }
+}
+
+// One x range and bin count for every group, so the histograms share their bin width
+fn shared_bins(samples: &[Sample]) -> ((f64, f64), usize) {
+    let pooled: Vec<f64> = samples.iter().flat_map(|g| g.values.clone()).collect();
+    (range(pooled.iter().copied()), bin_count(&pooled))
+}
+
+// Histogram of one sample's values
+pub fn histogram_chart(g: &Sample, x_range: (f64, f64), bins: usize, color: Rgb) -> Chart {
+    let (lo, hi) = x_range;
+    let counts = histogram(&g.values, lo, hi, bins);
+
+    Chart {
+        title: format!("Histogram {}", g.name),
+        x_label: g.name.clone(),
+        y_label: "Count".to_string(),
+        x_range,
+        y_range: (0.0, counts.iter().copied().fold(1.0, f64::max) * 1.1),
+        categories: Vec::new(),
+        items: vec![Item::Bars {
+            lo,
+            width: (hi - lo) / bins as f64,
+            counts,
+            color,
+        }],
+    }
+}
+
+// Bootstrap distribution of the mean difference with its percentile bounds and zero marked
+// The bounds come from the plotted distribution, unpaired data reports a wider CI built from each group's own,
+// so the lines are labelled as percentiles rather than as the reported CI
+pub fn boot_diff_chart(c: &Comparison) -> Chart {
+    let s = &c.sdmean;
+    let clevel = c.settings.clevel();
+    let (low, high) = (
+        quantile(&c.boot_diff, clevel),
+        quantile(&c.boot_diff, 1.0 - clevel),
+    );
+    let pct = |q: f64| format!("{} Pctl", science_pretty_format(q * 100.0, 3));
+    let (lo, hi) = range(c.boot_diff.iter().copied().chain([low, high, 0.0]));
+    let bins = 50;
+    let counts = histogram(&c.boot_diff, lo, hi, bins);
+
+    Chart {
+        title: format!(
+            "Bootstrap Mean Diff ({} - {})",
+            c.samples[1].name, c.samples[0].name
+        ),
+        x_label: "Mean Diff, dashed at percentiles of the resamples shown".to_string(),
+        y_label: "Count".to_string(),
+        x_range: (lo, hi),
+        y_range: (0.0, counts.iter().copied().fold(1.0, f64::max) * 1.15),
+        categories: Vec::new(),
+        items: vec![
+            Item::Bars {
+                lo,
+                width: (hi - lo) / bins as f64,
+                counts,
+                color: SHADE,
+            },
+            Item::VLine {
+                x: 0.0,
+                label: "0".to_string(),
+                color: BLACK,
+                dashed: false,
+            },
+            Item::VLine {
+                x: low,
+                label: pct(clevel),
+                color: RED,
+                dashed: true,
+            },
+            Item::VLine {
+                x: high,
+                label: pct(1.0 - clevel),
+                color: RED,
+                dashed: true,
+            },
+            Item::VLine {
+                x: s.dmm,
+                label: "Mean".to_string(),
+                color: BLUE,
+                dashed: false,
+            },
+        ],
+    }
+}
+
+// Normal Q-Q plot, points near the line through mean and SD suggest normality
+pub fn qq_chart(g: &Sample, color: Rgb) -> Chart {
+    let mut y = g.values.clone();
+    y.sort_by(cmp_f64);
+
+    // Blom plotting positions
+    let n = y.len() as f64;
+    let x: Vec<f64> = (0..y.len())
+        .map(|i| z_from_p((i as f64 + 1.0 - 0.375) / (n + 0.25)))
+        .collect();
+
+    let m = mean(&y);
+    let sd = sd_sample(&y, &m);
+    let x_range = range(x.iter().copied());
+
+    Chart {
+        title: format!("Normal Q-Q {}", g.name),
+        x_label: "Normal Quantile".to_string(),
+        y_label: g.name.clone(),
+        x_range,
+        y_range: range(y.iter().copied()),
+        categories: Vec::new(),
+        items: vec![
+            Item::Segment {
+                x1: x_range.0,
+                y1: m + sd * x_range.0,
+                x2: x_range.1,
+                y2: m + sd * x_range.1,
+                color: GREY,
+                dashed: true,
+            },
+            Item::Points { x, y, color },
+        ],
+    }
+}
+
+// Overlaid empirical CDFs, with the largest gap between A and B marked
+pub fn ecdf_chart(samples: &[Sample]) -> Chart {
+    let x_range = range(samples.iter().flat_map(|g| g.values.iter().copied()));
+    let sorted: Vec<Vec<f64>> = samples
+        .iter()
+        .map(|g| {
+            let mut v = g.values.clone();
+            v.sort_by(cmp_f64);
+            v
+        })
+        .collect();
+    let mut items: Vec<Item> = Vec::new();
+
+    for (i, v) in sorted.iter().enumerate() {
+        let n = v.len() as f64;
+        let mut x: Vec<f64> = vec![x_range.0];
+        let mut y: Vec<f64> = vec![0.0];
+
+        // Step up at every value
+        for (j, xv) in v.iter().enumerate() {
+            x.extend([*xv, *xv]);
+            y.extend([j as f64 / n, (j + 1) as f64 / n]);
+        }
+        x.push(x_range.1);
+        y.push(1.0);
+
+        items.push(Item::Polyline {
+            x,
+            y,
+            color: PALETTE[i % PALETTE.len()],
+        });
+    }
+
+    let mut entries: Vec<(String, Rgb)> = samples
+        .iter()
+        .enumerate()
+        .map(|(i, g)| (g.name.clone(), PALETTE[i % PALETTE.len()]))
+        .collect();
+
+    // Kolmogorov-Smirnov distance, the ECDFs only change at data values
+    if sorted.len() == 2 {
+        let ecdf = |v: &[f64], x: f64| v.partition_point(|a| *a <= x) as f64 / v.len() as f64;
+        let (a, b) = (&sorted[0], &sorted[1]);
+        let (at, d) = a
+            .iter()
+            .chain(b.iter())
+            .map(|x| (*x, (ecdf(a, *x) - ecdf(b, *x)).abs()))
+            .fold((0.0, -1.0), |best, t| if t.1 > best.1 { t } else { best });
+
+        items.push(Item::Segment {
+            x1: at,
+            y1: ecdf(a, at),
+            x2: at,
+            y2: ecdf(b, at),
+            color: BLACK,
+            dashed: true,
+        });
+        entries.push((format!("D = {}", science_pretty_format(d, 3)), BLACK));
+    }
+
+    items.push(Item::Legend { entries });
+
+    Chart {
+        title: "ECDF".to_string(),
+        x_label: "Value".to_string(),
+        y_label: "Cumulative Fraction".to_string(),
+        x_range,
+        y_range: (0.0, 1.05),
+        categories: Vec::new(),
+        items,
+    }
+}
+
+// Bland-Altman plot of differences against means with the bias and limits of agreement
+pub fn bland_altman(ba: &Blandaltman) -> Chart {
+    let x_range = range(ba.means.iter().copied());
+    let y_range = range(
+        ba.diffs
+            .iter()
+            .copied()
+            .chain([ba.lower.l, ba.upper.u, 0.0]),
+    );
+
+    Chart {
+        title: "Bland-Altman".to_string(),
+        x_label: "Mean of A and B".to_string(),
+        y_label: "B - A".to_string(),
+        x_range,
+        y_range,
+        categories: Vec::new(),
+        items: vec![
+            Item::HBand {
+                y1: ba.lower.l,
+                y2: ba.lower.u,
+                color: SHADE,
+            },
+            Item::HBand {
+                y1: ba.upper.l,
+                y2: ba.upper.u,
+                color: SHADE,
+            },
+            Item::HBand {
+                y1: ba.bias.l,
+                y2: ba.bias.u,
+                color: SHADE,
+            },
+            Item::HLine {
+                y: 0.0,
+                label: String::new(),
+                color: GREY,
+                dashed: false,
+            },
+            Item::HLine {
+                y: ba.upper.value,
+                label: format!("+{} SD {}", LOA_Z, science_pretty_format(ba.upper.value, 3)),
+                color: RED,
+                dashed: true,
+            },
+            Item::HLine {
+                y: ba.bias.value,
+                label: format!("Bias {}", science_pretty_format(ba.bias.value, 3)),
+                color: BLUE,
+                dashed: false,
+            },
+            Item::HLine {
+                y: ba.lower.value,
+                label: format!("-{} SD {}", LOA_Z, science_pretty_format(ba.lower.value, 3)),
+                color: RED,
+                dashed: true,
+            },
+            Item::Segment {
+                x1: x_range.0,
+                y1: ba.intercept + ba.slope.value * x_range.0,
+                x2: x_range.1,
+                y2: ba.intercept + ba.slope.value * x_range.1,
+                color: GREEN,
+                dashed: true,
+            },
+            Item::Points {
+                x: ba.means.clone(),
+                y: ba.diffs.clone(),
+                color: BLACK,
+            },
+        ],
+    }
+}
+
+// Render a chart onto a canvas of the given size
+pub fn draw(chart: &Chart, c: &mut dyn Canvas, w: f64, h: f64) {
+    let (left, right, top, bottom) = (62.0, 16.0, 30.0, 44.0);
+    let pw = w - left - right;
+    let ph = h - top - bottom;
+    let (x0, x1) = chart.x_range;
+    let (y0, y1) = chart.y_range;
+
+    let px = |x: f64| left + (x - x0) / (x1 - x0) * pw;
+    let py = |y: f64| top + ph - (y - y0) / (y1 - y0) * ph;
+
+    c.rect(0.0, 0.0, w, h, (255, 255, 255), true);
+
+    // Grid and tick labels
+    if chart.categories.is_empty() {
+        for t in ticks(x0, x1, 6) {
+            c.line(px(t), top, px(t), top + ph, SHADE, false);
+            c.text(
+                px(t),
+                top + ph + 16.0,
+                &science_pretty_format(t, 3),
+                Anchor::Middle,
+                BLACK,
+            );
+        }
+    } else {
+        for (i, name) in chart.categories.iter().enumerate() {
+            let t = (i + 1) as f64;
+            c.text(px(t), top + ph + 16.0, name, Anchor::Middle, BLACK);
+        }
+    }
+    for t in ticks(y0, y1, 6) {
+        c.line(left, py(t), left + pw, py(t), SHADE, false);
+        c.text(
+            left - 5.0,
+            py(t) + 4.0,
+            &science_pretty_format(t, 3),
+            Anchor::End,
+            BLACK,
+        );
+    }
+
+    // Count vertical lines so their labels can be staggered
+    let mut vlines = 0;
+
+    for item in &chart.items {
+        match item {
+            Item::Points { x, y, color } => {
+                for (xv, yv) in x.iter().zip(y.iter()) {
+                    c.circle(px(*xv), py(*yv), 3.0, *color);
+                }
+            }
+            Item::Segment {
+                x1,
+                y1,
+                x2,
+                y2,
+                color,
+                dashed,
+            } => {
+                if let Some((x1, y1, x2, y2)) = clip(*x1, *y1, *x2, *y2, chart) {
+                    c.line(px(x1), py(y1), px(x2), py(y2), *color, *dashed);
+                }
+            }
+            Item::HLine {
+                y,
+                label,
+                color,
+                dashed,
+            } => {
+                c.line(left, py(*y), left + pw, py(*y), *color, *dashed);
+                if !label.is_empty() {
+                    c.text(left + pw - 4.0, py(*y) - 4.0, label, Anchor::End, *color);
+                }
+            }
+            Item::HBand { y1, y2, color } => {
+                let (a, b) = (py(*y1).min(py(*y2)), py(*y1).max(py(*y2)));
+                c.rect(left, a, pw, b - a, *color, true);
+            }
+            Item::VLine {
+                x,
+                label,
+                color,
+                dashed,
+            } => {
+                c.line(px(*x), top, px(*x), top + ph, *color, *dashed);
+                if !label.is_empty() {
+                    let y = top + 12.0 + 14.0 * (vlines % 3) as f64;
+                    // Labels near the right edge go on the left of their line
+                    if px(*x) > left + pw * 0.8 {
+                        c.text(px(*x) - 3.0, y, label, Anchor::End, *color);
+                    } else {
+                        c.text(px(*x) + 3.0, y, label, Anchor::Start, *color);
+                    }
+                }
+                vlines += 1;
+            }
+            Item::Bars {
+                lo,
+                width,
+                counts,
+                color,
+            } => {
+                for (i, n) in counts.iter().enumerate() {
+                    let x = lo + i as f64 * width;
+                    let (bx, by) = (px(x), py(*n));
+                    let (bw, bh) = (px(x + width) - bx, py(0.0) - by);
+                    c.rect(bx, by, bw, bh, *color, true);
+                    c.rect(bx, by, bw, bh, GREY, false);
+                }
+            }
+            Item::Polyline { x, y, color } => {
+                for i in 1..x.len().min(y.len()) {
+                    c.line(
+                        px(x[i - 1]),
+                        py(y[i - 1]),
+                        px(x[i]),
+                        py(y[i]),
+                        *color,
+                        false,
+                    );
+                }
+            }
+            Item::Box {
+                x,
+                width,
+                q1,
+                median,
+                q3,
+                lo,
+                hi,
+                color,
+            } => {
+                let (l, r) = (px(x - width / 2.0), px(x + width / 2.0));
+                let (cx, cap) = (px(*x), (r - l) / 4.0);
+                c.line(cx, py(*lo), cx, py(*q1), *color, false);
+                c.line(cx, py(*q3), cx, py(*hi), *color, false);
+                c.line(cx - cap, py(*lo), cx + cap, py(*lo), *color, false);
+                c.line(cx - cap, py(*hi), cx + cap, py(*hi), *color, false);
+                c.rect(l, py(*q3), r - l, py(*q1) - py(*q3), (255, 255, 255), true);
+                c.rect(l, py(*q3), r - l, py(*q1) - py(*q3), *color, false);
+                c.line(l, py(*median), r, py(*median), *color, false);
+            }
+            Item::Legend { entries } => {
+                for (i, (label, color)) in entries.iter().enumerate() {
+                    let y = top + 16.0 + 16.0 * i as f64;
+                    c.line(left + 8.0, y - 4.0, left + 28.0, y - 4.0, *color, false);
+                    c.text(left + 34.0, y, label, Anchor::Start, *color);
+                }
+            }
+        }
+    }
+
+    // Axes and labels
+    c.rect(left, top, pw, ph, BLACK, false);
+    c.text(w / 2.0, 20.0, &chart.title, Anchor::Middle, BLACK);
+    c.text(
+        left + pw / 2.0,
+        h - 8.0,
+        &chart.x_label,
+        Anchor::Middle,
+        BLACK,
+    );
+    c.vtext(14.0, top + ph / 2.0, &chart.y_label, BLACK);
+}
+
+// Clip a segment to the chart ranges, Liang-Barsky
+fn clip(x1: f64, y1: f64, x2: f64, y2: f64, chart: &Chart) -> Option<(f64, f64, f64, f64)> {
+    let (dx, dy) = (x2 - x1, y2 - y1);
+    let (mut t0, mut t1) = (0.0, 1.0);
+
+    for (p, q) in [
+        (-dx, x1 - chart.x_range.0),
+        (dx, chart.x_range.1 - x1),
+        (-dy, y1 - chart.y_range.0),
+        (dy, chart.y_range.1 - y1),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            t0 = f64::max(t0, q / p);
+        } else {
+            t1 = f64::min(t1, q / p);
+        }
+    }
+
+    if t0 > t1 {
+        return None;
+    }
+    Some((x1 + t0 * dx, y1 + t0 * dy, x1 + t1 * dx, y1 + t1 * dy))
+}
+
+// Range of some values with a little padding on each side
+pub fn range<I: Iterator<Item = f64>>(values: I) -> (f64, f64) {
+    let (lo, hi) = values
+        .filter(|v| v.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(v), hi.max(v))
+        });
+
+    if lo > hi {
+        return (0.0, 1.0);
+    }
+    if lo == hi {
+        return (lo - 1.0, hi + 1.0);
+    }
+
+    let pad = (hi - lo) * 0.05;
+    (lo - pad, hi + pad)
+}
+
+// Pick round tick values covering a range
+fn ticks(lo: f64, hi: f64, n: usize) -> Vec<f64> {
+    let span = hi - lo;
+    let mut out: Vec<f64> = Vec::new();
+
+    if span <= 0.0 || !span.is_finite() {
+        return out;
+    }
+
+    let mag = 10f64.powf((span / n as f64).log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|m| m * mag)
+        .find(|s| span / s <= n as f64)
+        .unwrap_or(10.0 * mag);
+
+    let mut t = (lo / step).ceil() * step;
+    while t <= hi {
+        // Snap values like 0.30000000000000004 back to round numbers
+        out.push((t / step).round() * step);
+        t += step;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boot_diff_lines_are_labelled_percentiles() {
+        let a = Sample {
+            name: "A".to_string(),
+            values: vec![1.0, 2.0, 3.0, 4.0, 9.0],
+        };
+        let b = Sample {
+            name: "B".to_string(),
+            values: vec![3.0, 4.0, 5.0, 7.0, 6.0],
+        };
+        let s = Settings {
+            iterations: 1000,
+            seed: Some(2),
+            ..Settings::default()
+        };
+        let c = compare(&a, &b, &s).unwrap();
+        let chart = boot_diff_chart(&c);
+
+        let dashed: Vec<(f64, &str)> = chart
+            .items
+            .iter()
+            .filter_map(|i| match i {
+                Item::VLine {
+                    x, label, dashed, ..
+                } if *dashed => Some((*x, label.as_str())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(dashed.len(), 2);
+        assert_eq!(dashed[0], (quantile(&c.boot_diff, 0.05), "5 Pctl"));
+        assert_eq!(dashed[1].1, "95 Pctl");
+    }
+}
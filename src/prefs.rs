@@ -0,0 +1,227 @@
+use crate::analysis::Settings;
+use crate::input::Numberformat;
+use serde::{Deserialize, Serialize};
+
+use std::path::{Path, PathBuf};
+
+// Main window size on first launch and after a reset
+pub const WINDOW_W: i32 = 737;
+pub const WINDOW_H: i32 = 555;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+// Define a struct for the defaults restored at startup
+pub struct Prefs {
+    pub settings: Settings,
+    pub strict: bool,
+    pub numbers: Numberformat,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Default for Prefs {
+    fn default() -> Self {
+        Prefs {
+            settings: Settings::default(),
+            strict: false,
+            numbers: Numberformat::default(),
+            width: WINDOW_W,
+            height: WINDOW_H,
+        }
+    }
+}
+
+// Read the saved defaults, a missing or unreadable file gives the built in ones
+pub fn load_prefs() -> Prefs {
+    prefs_file()
+        .and_then(|f| std::fs::read_to_string(f).ok())
+        .and_then(|text| toml::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+// Write the defaults for the next launch
+pub fn save_prefs(prefs: &Prefs) -> Result<(), String> {
+    let file = prefs_file().ok_or("No Config Directory")?;
+    let text = toml::to_string(prefs).map_err(|e| e.to_string())?;
+
+    if let Some(dir) = file.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    std::fs::write(&file, text).map_err(|e| format!("{}: {}", file.display(), e))
+}
+
+// Per user settings directory, $XDG_CONFIG_HOME/bootstrapci on Linux
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("bootstrapci"))
+}
+
+fn prefs_file() -> Option<PathBuf> {
+    config_dir().map(|d| d.join("prefs.toml"))
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+// Define a struct for a named bundle of form settings, one TOML file each so they can be shared
+pub struct Preset {
+    pub name: String,
+    pub settings: Settings,
+    pub strict: bool,
+    pub numbers: Numberformat,
+}
+
+impl Default for Preset {
+    fn default() -> Self {
+        let prefs = Prefs::default();
+
+        Preset {
+            name: String::new(),
+            settings: prefs.settings,
+            strict: prefs.strict,
+            numbers: prefs.numbers,
+        }
+    }
+}
+
+// The saved presets sorted by name, files that do not parse are left out
+pub fn presets() -> Vec<Preset> {
+    let mut out: Vec<Preset> = presets_dir()
+        .and_then(|d| std::fs::read_dir(d).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|x| x == "toml"))
+        .filter_map(|e| read_preset(&e.path()).ok())
+        .collect();
+
+    out.sort_by_key(|p| p.name.to_lowercase());
+    out
+}
+
+// Find a saved preset by name, ignoring case
+pub fn find_preset(name: &str) -> Option<Preset> {
+    presets()
+        .into_iter()
+        .find(|p| p.name.eq_ignore_ascii_case(name))
+}
+
+// Read a preset file, a file without a name is named after the file
+pub fn read_preset(path: &Path) -> Result<Preset, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut preset: Preset =
+        toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    if preset.name.trim().is_empty() {
+        preset.name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+    }
+    Ok(preset)
+}
+
+// Write a preset to any file, for sharing, presets are shared so like the defaults they leave out the seed
+pub fn write_preset(preset: &Preset, path: &Path) -> Result<(), String> {
+    let preset = Preset {
+        settings: Settings {
+            seed: None,
+            ..preset.settings.clone()
+        },
+        ..preset.clone()
+    };
+    let text = toml::to_string(&preset).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// Keep a preset with the saved ones, replacing one of the same name
+pub fn save_preset(preset: &Preset) -> Result<(), String> {
+    if preset.name.trim().is_empty() {
+        return Err("Preset Needs A Name".to_string());
+    }
+    let dir = presets_dir().ok_or("No Config Directory")?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+
+    delete_preset(&preset.name);
+    write_preset(preset, &dir.join(preset_file(&preset.name)))
+}
+
+// Remove a saved preset by name
+pub fn delete_preset(name: &str) {
+    let Some(dir) = presets_dir() else {
+        return;
+    };
+    for e in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+        if read_preset(&e.path()).is_ok_and(|p| p.name.eq_ignore_ascii_case(name)) {
+            let _ = std::fs::remove_file(e.path());
+        }
+    }
+}
+
+// File name for a preset, characters that are not safe in file names become underscores
+fn preset_file(name: &str) -> String {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == ' ' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}.toml", stem)
+}
+
+fn presets_dir() -> Option<PathBuf> {
+    config_dir().map(|d| d.join("presets"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefs_fill_in_missing_keys() {
+        // A file from an older version keeps its values and takes the defaults for the rest
+        let p: Prefs = toml::from_str("strict = true\n[settings]\nconfidence = 90.0\n").unwrap();
+        assert!(p.strict);
+        assert_eq!(p.settings.confidence, 90.0);
+        assert_eq!(p.settings.iterations, Settings::default().iterations);
+        assert_eq!((p.width, p.height), (WINDOW_W, WINDOW_H));
+
+        let p = Prefs {
+            width: 900,
+            ..Prefs::default()
+        };
+        let back: Prefs = toml::from_str(&toml::to_string(&p).unwrap()).unwrap();
+        assert_eq!((back.width, back.numbers), (900, p.numbers));
+    }
+
+    #[test]
+    fn presets_leave_out_the_seed() {
+        let path = std::env::temp_dir().join("preset_seed.toml");
+        let preset = Preset {
+            name: "Quick".to_string(),
+            settings: Settings {
+                iterations: 2000,
+                seed: Some(42),
+                ..Settings::default()
+            },
+            ..Preset::default()
+        };
+        write_preset(&preset, &path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let back = read_preset(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert!(!text.contains("seed"));
+        assert_eq!(back.name, "Quick");
+        assert_eq!(back.settings.iterations, 2000);
+        assert_eq!(back.settings.seed, None);
+    }
+
+    #[test]
+    fn preset_file_names_are_safe() {
+        assert_eq!(preset_file(" a/b: c "), "a_b_ c.toml");
+    }
+}
@@ -0,0 +1,263 @@
+use crate::analysis::*;
+use crate::apa::{apa_multi_summary, apa_summary};
+use crate::document::*;
+use crate::stats::*;
+use numpy::{AllowTypeChange, PyArrayLike1};
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyString};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Serialize;
+use serde_json::Value;
+
+// A one dimensional array of any numeric type, NaN or None marks a missing value
+type Values<'py> = PyArrayLike1<'py, f64, AllowTypeChange>;
+
+// Python bindings for the statistics engine, the same calculations as the GUI and the command line
+#[pymodule]
+#[pyo3(name = "bootstrapci")]
+fn bootstrapci(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(bootstrap_ci, m)?)?;
+    m.add_function(wrap_pyfunction!(compare_samples, m)?)?;
+    m.add_function(wrap_pyfunction!(compare_groups, m)?)?;
+    m.add_function(wrap_pyfunction!(tests, m)?)?;
+    Ok(())
+}
+
+// Bootstrapped mean and SD with their confidence intervals for one array
+#[pyfunction]
+#[pyo3(name = "ci", signature = (values, **settings))]
+fn bootstrap_ci<'py>(
+    py: Python<'py>,
+    values: Values<'py>,
+    settings: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let s = read_settings(settings)?;
+    let v: Vec<f64> = sample(&values, "A")
+        .values
+        .into_iter()
+        .filter(|x| !x.is_nan())
+        .collect();
+    if v.is_empty() {
+        return Err(PyValueError::new_err("Data Field Needs At Least One Value"));
+    }
+
+    let seed = draw_seed(s.seed);
+    let c = ci(
+        &v,
+        s.iterations,
+        s.clevel(),
+        &mut StdRng::seed_from_u64(seed),
+    );
+
+    let d = PyDict::new(py);
+    d.set_item("count", v.len())?;
+    d.set_item("mean", c.mm)?;
+    d.set_item("mean_low", c.ml)?;
+    d.set_item("mean_high", c.mu)?;
+    d.set_item("sd", c.sm)?;
+    d.set_item("sd_low", c.sl)?;
+    d.set_item("sd_high", c.su)?;
+    d.set_item("seed", seed)?;
+    Ok(d)
+}
+
+// Compare A and B, paired or unpaired, as the full result, its APA summary and the report sections
+#[pyfunction]
+#[pyo3(name = "compare", signature = (a, b, **settings))]
+fn compare_samples<'py>(
+    py: Python<'py>,
+    a: Values<'py>,
+    b: Values<'py>,
+    settings: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let s = read_settings(settings)?;
+    let c = compare(&sample(&a, "A"), &sample(&b, "B"), &s).map_err(PyValueError::new_err)?;
+
+    answer(
+        py,
+        "two_sample",
+        apa_summary(&c),
+        &c,
+        &comparison_document(&c),
+    )
+}
+
+// Compare any number of groups, two get the A / B comparison and more the k sample one, like the reports
+#[pyfunction]
+#[pyo3(signature = (groups, names = None, **settings))]
+fn compare_groups<'py>(
+    py: Python<'py>,
+    groups: Vec<Values<'py>>,
+    names: Option<Vec<String>>,
+    settings: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let s = read_settings(settings)?;
+    if groups.len() < 2 {
+        return Err(PyValueError::new_err("Need At Least Two Groups"));
+    }
+    let names = names.unwrap_or_default();
+    let samples: Vec<Sample> = groups
+        .iter()
+        .enumerate()
+        .map(|(i, g)| {
+            let name = names.get(i).cloned().unwrap_or_else(|| group_name(i));
+            sample(g, &name)
+        })
+        .collect();
+
+    if samples.len() == 2 {
+        let c = compare(&samples[0], &samples[1], &s).map_err(PyValueError::new_err)?;
+        answer(
+            py,
+            "two_sample",
+            apa_summary(&c),
+            &c,
+            &comparison_document(&c),
+        )
+    } else {
+        let m = compare_many(&samples, &s).map_err(PyValueError::new_err)?;
+        answer(
+            py,
+            "multi_sample",
+            apa_multi_summary(&m),
+            &m,
+            &multi_document(&m),
+        )
+    }
+}
+
+// The hypothesis tests of an A / B comparison keyed by the report's test names
+#[pyfunction]
+#[pyo3(signature = (a, b, **settings))]
+fn tests<'py>(
+    py: Python<'py>,
+    a: Values<'py>,
+    b: Values<'py>,
+    settings: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let s = read_settings(settings)?;
+    let c = compare(&sample(&a, "A"), &sample(&b, "B"), &s).map_err(PyValueError::new_err)?;
+    let doc = comparison_document(&c);
+
+    // The verdict is the last column of the report row, worded as the GUI shows it
+    let verdict = |name: &str| {
+        doc.sections
+            .iter()
+            .filter(|sec| sec.title == "Hypothesis Tests")
+            .flat_map(|sec| &sec.grid.rows)
+            .find(|r| r.first().is_some_and(|t| t == name))
+            .and_then(|r| r.last().cloned())
+            .unwrap_or_default()
+    };
+
+    let d = PyDict::new(py);
+    for (name, stat, p, adj_p) in [
+        ("Mean Diff", c.sdmean.dmm, c.mean_p, c.adj_mean_p),
+        ("SD Diff", c.sdmean.dsm, c.sd_p, c.adj_sd_p),
+        ("F-Test", c.f, c.f_p, c.adj_f_p),
+    ] {
+        let t = PyDict::new(py);
+        t.set_item("statistic", stat)?;
+        t.set_item("p", p)?;
+        t.set_item("adj_p", adj_p)?;
+        t.set_item("result", verdict(name))?;
+        d.set_item(name, t)?;
+    }
+    Ok(d)
+}
+
+// Settings from keyword arguments, anything left out keeps the GUI default
+fn read_settings(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Settings> {
+    let mut s = Settings::default();
+
+    for (k, v) in kwargs.into_iter().flat_map(|d| d.iter()) {
+        let key: String = k.extract()?;
+        match key.as_str() {
+            "paired" => s.paired = v.extract()?,
+            "two_tailed" => s.two_tailed = v.extract()?,
+            "confidence" => s.confidence = v.extract()?,
+            "iterations" => s.iterations = v.extract()?,
+            "zthresh" => s.zthresh = v.extract()?,
+            "adjust" => {
+                s.adjust =
+                    Adjust::from_name(&v.extract::<String>()?).map_err(PyValueError::new_err)?
+            }
+            "missing" => {
+                s.missing =
+                    Missing::from_name(&v.extract::<String>()?).map_err(PyValueError::new_err)?
+            }
+            "seed" => s.seed = v.extract()?,
+            k => return Err(PyTypeError::new_err(format!("Unknown setting {}", k))),
+        }
+    }
+    s.check().map_err(PyValueError::new_err)?;
+    Ok(s)
+}
+
+fn sample(values: &Values<'_>, name: &str) -> Sample {
+    Sample {
+        name: name.to_string(),
+        values: values.as_array().iter().copied().collect(),
+    }
+}
+
+// A comparison as a dict, the report sections map their titles to rows of header and cell
+fn answer<'py, T: Serialize>(
+    py: Python<'py>,
+    kind: &str,
+    summary: Vec<String>,
+    result: &T,
+    doc: &Document,
+) -> PyResult<Bound<'py, PyDict>> {
+    let result = serde_json::to_value(result).map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+    let report = PyDict::new(py);
+    for sec in &doc.sections {
+        let rows = PyList::empty(py);
+        for r in &sec.grid.rows {
+            let row = PyDict::new(py);
+            for (h, cell) in sec.grid.header.iter().zip(r) {
+                row.set_item(h, cell)?;
+            }
+            rows.append(row)?;
+        }
+        report.set_item(&sec.title, rows)?;
+    }
+
+    let d = PyDict::new(py);
+    d.set_item("kind", kind)?;
+    d.set_item("summary", summary)?;
+    d.set_item("result", to_python(py, &result)?)?;
+    d.set_item("report", report)?;
+    Ok(d)
+}
+
+// Convert a JSON value to the matching Python object, NaN was already written as null and becomes None
+fn to_python<'py>(py: Python<'py>, v: &Value) -> PyResult<Bound<'py, PyAny>> {
+    Ok(match v {
+        Value::Null => py.None().into_bound(py),
+        Value::Bool(b) => b.into_pyobject(py)?.to_owned().into_any(),
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(u), _) => u.into_pyobject(py)?.into_any(),
+            (_, Some(i)) => i.into_pyobject(py)?.into_any(),
+            _ => n.as_f64().unwrap_or(f64::NAN).into_pyobject(py)?.into_any(),
+        },
+        Value::String(s) => PyString::new(py, s).into_any(),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(to_python(py, item)?)?;
+            }
+            list.into_any()
+        }
+        Value::Object(map) => {
+            let d = PyDict::new(py);
+            for (k, item) in map {
+                d.set_item(k, to_python(py, item)?)?;
+            }
+            d.into_any()
+        }
+    })
+}
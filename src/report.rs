@@ -0,0 +1,987 @@
+use crate::analysis::*;
+use crate::batch::Batch;
+use crate::document::bounds;
+use crate::input::Rejected;
+use crate::stats::*;
+
+// Build the text report for a two sample comparison
+pub fn two_sample(c: &Comparison) -> String {
+    // Output String
+    let mut out: String = String::from("");
+
+    let s = &c.sdmean;
+    let clevel = c.settings.clevel();
+    let mean_a = s.amm;
+    let mean_b = s.bmm;
+    let mean_d = s.dmm;
+    let sd_a = s.asm;
+    let sd_b = s.bsm;
+    let sd_d = s.dsm;
+
+    out.push_str(&format!("Count A: \t{}\n", c.a.count));
+    out.push_str(&format!("Count B: \t{}\n", c.b.count));
+
+    missing_section(&mut out, &c.settings, &c.missing, &["A", "B"]);
+    adjust_header(&mut out, &c.settings, c.tests);
+
+    out.push_str(&format!(
+        "\nMin A:    \t{}\n",
+        &science_pretty_format(c.a.min, 6)
+    ));
+    out.push_str(&format!(
+        "Max A:    \t{}\n",
+        &science_pretty_format(c.a.max, 6)
+    ));
+    out.push_str(&format!(
+        "\nMin B:    \t{}\n",
+        &science_pretty_format(c.b.min, 6)
+    ));
+    out.push_str(&format!(
+        "Max B:    \t{}\n",
+        &science_pretty_format(c.b.max, 6)
+    ));
+
+    out.push_str(&format!(
+        "\n+Z Count A:    \t{}   {}%\n",
+        c.a.zcount.pluscount,
+        &science_pretty_format(c.a.zcount.pluspercent, 1),
+    ));
+    out.push_str(&format!(
+        "-Z Count A:    \t{}   {}%\n",
+        c.a.zcount.minuscount,
+        &science_pretty_format(c.a.zcount.minuspercent, 1),
+    ));
+    out.push_str(&format!(
+        "\n+Z Count B:    \t{}   {}%\n",
+        c.b.zcount.pluscount,
+        &science_pretty_format(c.b.zcount.pluspercent, 1),
+    ));
+    out.push_str(&format!(
+        "-Z Count B:    \t{}   {}%\n",
+        c.b.zcount.minuscount,
+        &science_pretty_format(c.b.zcount.minuspercent, 1),
+    ));
+
+    out.push_str("\n************************************\n");
+
+    let mu = s.dmu;
+    let ml = s.dml;
+
+    // Handle one or two tailed data Mean
+    if c.settings.two_tailed {
+        // Two Tailed
+        let pv = c.mean_p;
+        out.push_str(&format!(
+            "CI Low A: \t{}\n",
+            &science_pretty_format(s.aml, 6)
+        ));
+        out.push_str(&format!(
+            "Mean A: \t{}\n",
+            &science_pretty_format(mean_a, 6)
+        ));
+        out.push_str(&format!(
+            "CI High A: \t{}\n",
+            &science_pretty_format(s.amu, 6)
+        ));
+        out.push_str(&format!(
+            "\nCI Low B: \t{}\n",
+            &science_pretty_format(s.bml, 6)
+        ));
+        out.push_str(&format!(
+            "Mean B: \t{}\n",
+            &science_pretty_format(mean_b, 6)
+        ));
+        out.push_str(&format!(
+            "CI High B: \t{}\n",
+            &science_pretty_format(s.bmu, 6)
+        ));
+        out.push('\n');
+
+        out.push_str(&format!(
+            "CI Low Diff: \t{}\n",
+            &science_pretty_format(ml, 6)
+        ));
+        out.push_str(&format!(
+            "Mean Diff: \t{}\n",
+            &science_pretty_format(mean_d, 6)
+        ));
+        out.push_str(&format!(
+            "CI High Diff: \t{}\n",
+            &science_pretty_format(mu, 6)
+        ));
+        out.push_str(&format!("\np-Value: \t{}\n", &science_pretty_format(pv, 3)));
+
+        if pv > clevel {
+            out.push_str("H0 = True \tA ≈ B\n");
+        } else if mean_a > mean_b {
+            out.push_str("H0 = False \tA > B\n");
+        } else {
+            out.push_str("H0 = False \tA < B\n");
+        }
+    } else {
+        // One Tailed
+        let pv = c.mean_p;
+
+        out.push_str(&format!(
+            "CI Low A: \t{}\n",
+            &science_pretty_format(s.aml, 6)
+        ));
+        out.push_str(&format!(
+            "Mean A: \t{}\n",
+            &science_pretty_format(mean_a, 6)
+        ));
+        out.push_str(&format!(
+            "CI High A: \t{}\n",
+            &science_pretty_format(s.amu, 6)
+        ));
+        out.push_str(&format!(
+            "\nCI Low B: \t{}\n",
+            &science_pretty_format(s.bml, 6)
+        ));
+        out.push_str(&format!(
+            "Mean B: \t{}\n",
+            &science_pretty_format(mean_b, 6)
+        ));
+        out.push_str(&format!(
+            "CI High B: \t{}\n",
+            &science_pretty_format(s.bmu, 6)
+        ));
+        out.push('\n');
+
+        if mean_a > mean_b {
+            out.push_str(&format!(
+                "CI Low Diff: \t{}\n",
+                &science_pretty_format(ml, 6)
+            ));
+            out.push_str(&format!(
+                "Mean Diff: \t{}\n",
+                &science_pretty_format(mean_d, 6)
+            ));
+            out.push_str(&format!("\np-Value: \t{}\n", &science_pretty_format(pv, 3)));
+            if pv > clevel {
+                out.push_str("H0 = True \tA ≈ B\n");
+            } else {
+                out.push_str("H0 = False \tA > B\n");
+            }
+        } else {
+            out.push_str(&format!(
+                "Mean Diff: \t{}\n",
+                &science_pretty_format(mean_d, 6)
+            ));
+            out.push_str(&format!(
+                "CI High Diff: \t{}\n",
+                &science_pretty_format(mu, 6)
+            ));
+            out.push_str(&format!("\np-Value: \t{}\n", &science_pretty_format(pv, 3)));
+            if pv > clevel {
+                out.push_str("H0 = True \tA ≈ B\n");
+            } else {
+                out.push_str("H0 = False \tA < B\n");
+            }
+        }
+    }
+
+    adjusted(&mut out, &c.settings, c.adj_mean_p, clevel);
+
+    out.push_str(&format!(
+        "\n% Change: \t{}\n",
+        &science_pretty_format(per_change(&mean_a, &mean_b), 1)
+    ));
+
+    out.push_str("\n************************************\n");
+
+    let su = s.dsu;
+    let sl = s.dsl;
+
+    // Handle one or two tailed data SD
+    if c.settings.two_tailed {
+        // Two Tailed
+        let pv = c.sd_p;
+
+        out.push_str(&format!(
+            "CI Low A:     \t{}\n",
+            &science_pretty_format(s.asl, 6)
+        ));
+        out.push_str(&format!(
+            "SD A:     \t{}\n",
+            &science_pretty_format(sd_a, 6)
+        ));
+        out.push_str(&format!(
+            "CI High A:     \t{}\n",
+            &science_pretty_format(s.asu, 6)
+        ));
+        out.push_str(&format!(
+            "\nCI Low B:     \t{}\n",
+            &science_pretty_format(s.bsl, 3)
+        ));
+        out.push_str(&format!(
+            "SD B:     \t{}\n",
+            &science_pretty_format(sd_b, 3)
+        ));
+        out.push_str(&format!(
+            "CI High B:     \t{}\n",
+            &science_pretty_format(s.bsu, 3)
+        ));
+        out.push('\n');
+
+        out.push_str(&format!(
+            "CI Low Diff: \t{}\n",
+            &science_pretty_format(sl, 6)
+        ));
+        out.push_str(&format!("SD Diff: \t{}\n", &science_pretty_format(sd_d, 6)));
+        out.push_str(&format!(
+            "CI High Diff: \t{}\n",
+            &science_pretty_format(su, 6)
+        ));
+        out.push_str(&format!("\np-Value: \t{}\n", &science_pretty_format(pv, 3)));
+
+        if pv > clevel {
+            out.push_str("H0 = True \tA ≈ B\n");
+        } else if sd_a > sd_b {
+            out.push_str("H0 = False \tA > B\n");
+        } else {
+            out.push_str("H0 = False \tA < B\n");
+        }
+    } else {
+        // One Tailed
+        let pv = c.sd_p;
+
+        out.push_str(&format!(
+            "CI Low A:     \t{}\n",
+            &science_pretty_format(s.asl, 6)
+        ));
+        out.push_str(&format!(
+            "SD A:     \t{}\n",
+            &science_pretty_format(sd_a, 6)
+        ));
+        out.push_str(&format!(
+            "CI High A:     \t{}\n",
+            &science_pretty_format(s.asu, 6)
+        ));
+        out.push_str(&format!(
+            "\nCI Low B:     \t{}\n",
+            &science_pretty_format(s.bsl, 3)
+        ));
+        out.push_str(&format!(
+            "SD B:     \t{}\n",
+            &science_pretty_format(sd_b, 3)
+        ));
+        out.push_str(&format!(
+            "CI High B:     \t{}\n",
+            &science_pretty_format(s.bsu, 3)
+        ));
+        out.push('\n');
+
+        if sd_a > sd_b {
+            out.push_str(&format!(
+                "CI Low Diff: \t{}\n",
+                &science_pretty_format(sl, 6)
+            ));
+            out.push_str(&format!("SD Diff: \t{}\n", &science_pretty_format(sd_d, 6)));
+            out.push_str(&format!("\np-Value: \t{}\n", &science_pretty_format(pv, 3)));
+            if pv > clevel {
+                out.push_str("H0 = True \tA ≈ B\n");
+            } else {
+                out.push_str("H0 = False \tA > B\n");
+            }
+        } else {
+            out.push_str(&format!("SD Diff: \t{}\n", &science_pretty_format(sd_d, 6)));
+            out.push_str(&format!(
+                "CI High Diff: \t{}\n",
+                &science_pretty_format(su, 6)
+            ));
+            out.push_str(&format!("\np-Value: \t{}\n", &science_pretty_format(pv, 3)));
+            if pv > clevel {
+                out.push_str("H0 = True \tA ≈ B\n");
+            } else {
+                out.push_str("H0 = False \tA < B\n");
+            }
+        }
+    }
+
+    adjusted(&mut out, &c.settings, c.adj_sd_p, clevel);
+
+    out.push_str(&format!(
+        "\n% Change: \t{}\n",
+        &science_pretty_format(per_change(&sd_a, &sd_b), 1)
+    ));
+
+    out.push_str("\n************************************\n");
+
+    out.push_str(&format!(
+        "Variance A:    \t{}\n",
+        &science_pretty_format(c.a.variance, 6)
+    ));
+    out.push_str(&format!(
+        "Variance B:    \t{}\n",
+        &science_pretty_format(c.b.variance, 6)
+    ));
+
+    out.push_str("\n************************************\n");
+
+    out.push_str(&format!(
+        "Median A:    \t{}\n",
+        &science_pretty_format(c.a.median, 6)
+    ));
+
+    out.push_str(&format!(
+        "Median B:    \t{}\n",
+        &science_pretty_format(c.b.median, 6)
+    ));
+
+    out.push_str(&format!(
+        "\n% Change: \t{}\n",
+        &science_pretty_format(per_change(&c.a.median, &c.b.median), 1)
+    ));
+
+    out.push_str("\n************************************\n");
+
+    match c.a.mode {
+        Some(m) => out.push_str(&format!("Mode A:    \t{}\n", &science_pretty_format(m, 6))),
+        None => out.push_str("Mode A:    \tNONE\n"),
+    }
+
+    match c.b.mode {
+        Some(m) => out.push_str(&format!("Mode B:    \t{}\n", &science_pretty_format(m, 6))),
+        None => out.push_str("Mode B:    \tNONE\n"),
+    }
+
+    out.push_str("\n************************************\n");
+
+    out.push_str(&format!(
+        "Cohen's d: \t{}\n",
+        &science_pretty_format(c.cohen_d, 2)
+    ));
+
+    out.push_str("\n************************************\n");
+    out.push_str(&format!("F-Test:   \t{}\n", &science_pretty_format(c.f, 4)));
+    out.push_str(&format!(
+        "\np-Value: \t{}\n",
+        &science_pretty_format(c.f_p, 4)
+    ));
+    if c.f_p <= clevel {
+        out.push_str("Sig:       \tSignificant\n");
+    } else {
+        out.push_str("Sig:       \tNot Significant\n");
+    }
+    adjusted(&mut out, &c.settings, c.adj_f_p, clevel);
+
+    out.push_str("\n************************************\n");
+
+    out.push_str(&format!(
+        "SE A:     \t{}\n",
+        &science_pretty_format(c.a.se, 6)
+    ));
+    out.push_str(&format!(
+        "SE B:     \t{}\n",
+        &science_pretty_format(c.b.se, 6)
+    ));
+
+    out.push_str("\n************************************\n");
+
+    out.push_str(&format!(
+        "Skewness A:    \t{}\n",
+        &science_pretty_format(c.a.skewness, 3)
+    ));
+    out.push_str(&format!(
+        "Skewness B:    \t{}\n",
+        &science_pretty_format(c.b.skewness, 3)
+    ));
+    out.push_str(&format!(
+        "\nKurtosis A:    \t{}\n",
+        &science_pretty_format(c.a.kurtosis, 3)
+    ));
+    out.push_str(&format!(
+        "Kurtosis B:    \t{}\n",
+        &science_pretty_format(c.b.kurtosis, 3)
+    ));
+
+    out.push_str("\n************************************\n");
+
+    // Check for paired correlation data
+    if let Some(corr) = &c.correlation {
+        out.push_str(&format!(
+            "Spearman's ρ: \t{}\n",
+            &science_pretty_format(corr.spearman, 2)
+        ));
+
+        out.push_str(&format!("Corr:      \t{}\n", corr_strength(corr.spearman)));
+
+        out.push_str(&format!(
+            "\np-Value: \t{}\n",
+            &science_pretty_format(corr.p, 3)
+        ));
+
+        if corr.p <= clevel {
+            out.push_str("Sig:       \tSignificant\n");
+        } else {
+            out.push_str("Sig:       \tNot Significant\n");
+        }
+        adjusted(&mut out, &c.settings, corr.adj_p, clevel);
+
+        out.push_str("\n************************************\n");
+
+        out.push_str(&format!("R²: \t{}\n", &science_pretty_format(corr.r2, 3)));
+
+        out.push_str("\n************************************\n");
+
+        corr_section(&mut out, &c.settings, "Kendall's τb", &corr.kendall, 3);
+        stat_section(&mut out, &c.settings, "Pearson r", &corr.pearson, 3);
+        stat_section(&mut out, &c.settings, "Slope B~A", &corr.slope, 6);
+        stat_section(&mut out, &c.settings, "Intercept", &corr.intercept, 6);
+    }
+
+    // Check for paired agreement data
+    if let Some(ba) = &c.blandaltman {
+        out.push_str("Bland-Altman (B - A)\n\n");
+
+        stat_section(&mut out, &c.settings, "Bias", &ba.bias, 6);
+
+        out.push_str(&format!(
+            "CI Low:   \t{}\n",
+            &science_pretty_format(ba.lower.l, 6)
+        ));
+        out.push_str(&format!(
+            "Lower LoA: \t{}\n",
+            &science_pretty_format(ba.lower.value, 6)
+        ));
+        out.push_str(&format!(
+            "CI High:  \t{}\n",
+            &science_pretty_format(ba.lower.u, 6)
+        ));
+        out.push_str(&format!(
+            "\nCI Low:   \t{}\n",
+            &science_pretty_format(ba.upper.l, 6)
+        ));
+        out.push_str(&format!(
+            "Upper LoA: \t{}\n",
+            &science_pretty_format(ba.upper.value, 6)
+        ));
+        out.push_str(&format!(
+            "CI High:  \t{}\n",
+            &science_pretty_format(ba.upper.u, 6)
+        ));
+
+        out.push_str("\n************************************\n");
+
+        stat_section(&mut out, &c.settings, "Prop Bias", &ba.slope, 6);
+    }
+
+    // Find and count unique values
+    out.push_str("Unique A Value Counts\n\n");
+
+    for (i, _) in c.a.unique_values.iter().enumerate() {
+        out.push_str(&format!(
+            "{}\t{}\n",
+            &science_pretty_format(c.a.unique_values[i], 6),
+            c.a.unique_counts[i]
+        ));
+    }
+
+    out.push_str("\n\nUnique B Value Counts\n\n");
+
+    for (i, _) in c.b.unique_values.iter().enumerate() {
+        out.push_str(&format!(
+            "{}\t{}\n",
+            &science_pretty_format(c.b.unique_values[i], 6),
+            c.b.unique_counts[i]
+        ));
+    }
+
+    out
+}
+
+// Build the text report for a k sample comparison
+pub fn multi_sample(m: &Multisample) -> String {
+    // Output String
+    let mut out: String = String::from("");
+
+    let clevel = m.settings.clevel();
+
+    out.push_str(&format!("Groups:    \t{}\n\n", m.names.len()));
+
+    for (i, g) in m.groups.iter().enumerate() {
+        out.push_str(&format!("Count {}: \t{}\n", m.names[i], g.count));
+    }
+
+    let names: Vec<&str> = m.names.iter().map(|n| n.as_str()).collect();
+    missing_section(&mut out, &m.settings, &m.missing, &names);
+    adjust_header(&mut out, &m.settings, m.tests);
+
+    out.push_str("\n************************************\n");
+
+    for (i, g) in m.groups.iter().enumerate() {
+        out.push_str(&format!(
+            "Min {}:    \t{}\n",
+            m.names[i],
+            &science_pretty_format(g.min, 6)
+        ));
+        out.push_str(&format!(
+            "Max {}:    \t{}\n\n",
+            m.names[i],
+            &science_pretty_format(g.max, 6)
+        ));
+    }
+
+    out.push_str("************************************\n");
+
+    for (i, c) in m.cis.iter().enumerate() {
+        out.push_str(&format!(
+            "CI Low {}: \t{}\n",
+            m.names[i],
+            &science_pretty_format(c.ml, 6)
+        ));
+        out.push_str(&format!(
+            "Mean {}: \t{}\n",
+            m.names[i],
+            &science_pretty_format(c.mm, 6)
+        ));
+        out.push_str(&format!(
+            "CI High {}: \t{}\n\n",
+            m.names[i],
+            &science_pretty_format(c.mu, 6)
+        ));
+    }
+
+    out.push_str("************************************\n");
+
+    for (i, c) in m.cis.iter().enumerate() {
+        out.push_str(&format!(
+            "CI Low {}:     \t{}\n",
+            m.names[i],
+            &science_pretty_format(c.sl, 6)
+        ));
+        out.push_str(&format!(
+            "SD {}:     \t{}\n",
+            m.names[i],
+            &science_pretty_format(c.sm, 6)
+        ));
+        out.push_str(&format!(
+            "CI High {}:     \t{}\n\n",
+            m.names[i],
+            &science_pretty_format(c.su, 6)
+        ));
+    }
+
+    out.push_str("************************************\n");
+
+    for (i, g) in m.groups.iter().enumerate() {
+        out.push_str(&format!(
+            "Median {}:    \t{}\n",
+            m.names[i],
+            &science_pretty_format(g.median, 6)
+        ));
+    }
+
+    out.push_str("\n************************************\n");
+
+    let alpha = m.settings.alpha();
+    out.push_str(&format!("{}\n\n", m.anova_name()));
+    out.push_str(&format!(
+        "F:        \t{}\n",
+        &science_pretty_format(m.anova.f, 4)
+    ));
+    out.push_str(&format!("df:       \t{}, {}\n", m.anova.df1, m.anova.df2));
+    out.push_str(&format!(
+        "\np-Value: \t{}\n",
+        &science_pretty_format(m.anova.p, 4)
+    ));
+    out.push_str(&format!(
+        "Boot p:   \t{}\n",
+        &science_pretty_format(m.anova.boot_p, 4)
+    ));
+    if m.anova.boot_p <= alpha {
+        out.push_str("Sig:       \tSignificant\n");
+    } else {
+        out.push_str("Sig:       \tNot Significant\n");
+    }
+    adjusted(&mut out, &m.settings, m.anova.adj_boot_p, alpha);
+
+    out.push_str("\n************************************\n");
+
+    let (test, stat) = m.rank_test();
+    out.push_str(&format!("{}\n\n", test));
+    out.push_str(&format!(
+        "{}:        \t{}\n",
+        stat,
+        &science_pretty_format(m.kruskal.h, 4)
+    ));
+    out.push_str(&format!("df:       \t{}\n", m.kruskal.df));
+    out.push_str(&format!(
+        "\np-Value: \t{}\n",
+        &science_pretty_format(m.kruskal.p, 4)
+    ));
+    if m.kruskal.p <= alpha {
+        out.push_str("Sig:       \tSignificant\n");
+    } else {
+        out.push_str("Sig:       \tNot Significant\n");
+    }
+    adjusted(&mut out, &m.settings, m.kruskal.adj_p, alpha);
+
+    out.push_str("\n************************************\n");
+
+    // Pairwise matrices, each cell is the column group compared to the row group
+    out.push_str("Pairwise Mean Diff (Col - Row)\n\n");
+    out.push_str(&pair_matrix(m, |p| science_pretty_format(p.diff.mm, 4)));
+
+    out.push_str("\nPairwise p-Values\n\n");
+    out.push_str(&pair_matrix(m, |p| science_pretty_format(p.p, 3)));
+
+    if m.settings.adjust != Adjust::None {
+        out.push_str("\nPairwise Adj p-Values\n\n");
+        out.push_str(&pair_matrix(m, |p| science_pretty_format(p.adj_p, 3)));
+    }
+
+    out.push_str("\n************************************\n");
+
+    for p in &m.pairs {
+        out.push_str(&format!("{} - {}\n", m.names[p.j], m.names[p.i]));
+        out.push_str(&format!(
+            "CI Low Diff: \t{}\n",
+            &science_pretty_format(p.diff.ml, 6)
+        ));
+        out.push_str(&format!(
+            "Mean Diff: \t{}\n",
+            &science_pretty_format(p.diff.mm, 6)
+        ));
+        out.push_str(&format!(
+            "CI High Diff: \t{}\n",
+            &science_pretty_format(p.diff.mu, 6)
+        ));
+        out.push_str(&format!("p-Value: \t{}\n", &science_pretty_format(p.p, 3)));
+
+        if p.p > clevel {
+            out.push_str(&format!(
+                "H0 = True \t{} ≈ {}\n",
+                m.names[p.i], m.names[p.j]
+            ));
+        } else if p.diff.mm < 0.0 {
+            out.push_str(&format!(
+                "H0 = False \t{} > {}\n",
+                m.names[p.i], m.names[p.j]
+            ));
+        } else {
+            out.push_str(&format!(
+                "H0 = False \t{} < {}\n",
+                m.names[p.i], m.names[p.j]
+            ));
+        }
+        adjusted(&mut out, &m.settings, p.adj_p, clevel);
+        out.push('\n');
+    }
+
+    out
+}
+
+// Show a bootstrapped statistic with its CI and significance
+fn stat_section(out: &mut String, s: &Settings, label: &str, st: &Statresults, digits: usize) {
+    stat_lines(out, s, label, st, digits, None);
+}
+
+// A correlation also gets its strength label, as Spearman's does
+fn corr_section(out: &mut String, s: &Settings, label: &str, st: &Statresults, digits: usize) {
+    stat_lines(out, s, label, st, digits, Some(corr_strength(st.value)));
+}
+
+fn stat_lines(
+    out: &mut String,
+    s: &Settings,
+    label: &str,
+    st: &Statresults,
+    digits: usize,
+    strength: Option<&str>,
+) {
+    out.push_str(&format!(
+        "CI Low:   \t{}\n",
+        &science_pretty_format(st.l, digits)
+    ));
+    out.push_str(&format!(
+        "{}: \t{}\n",
+        label,
+        &science_pretty_format(st.value, digits)
+    ));
+    out.push_str(&format!(
+        "CI High:  \t{}\n",
+        &science_pretty_format(st.u, digits)
+    ));
+    if let Some(strength) = strength {
+        out.push_str(&format!("Corr:      \t{}\n", strength));
+    }
+    out.push_str(&format!(
+        "\np-Value: \t{}\n",
+        &science_pretty_format(st.p, 3)
+    ));
+
+    if st.p <= s.clevel() {
+        out.push_str("Sig:       \tSignificant\n");
+    } else {
+        out.push_str("Sig:       \tNot Significant\n");
+    }
+    adjusted(out, s, st.adj_p, s.clevel());
+
+    out.push_str("\n************************************\n");
+}
+
+// Name the multiple comparison correction at the top of the report
+fn adjust_header(out: &mut String, s: &Settings, tests: usize) {
+    if s.adjust == Adjust::None {
+        return;
+    }
+
+    out.push_str(&format!(
+        "\nAdjust:    \t{}\nTests:     \t{}\n",
+        s.adjust.name(),
+        tests
+    ));
+}
+
+// Show the adjusted p value and its significance next to the raw one
+fn adjusted(out: &mut String, s: &Settings, adj_p: f64, clevel: f64) {
+    if s.adjust == Adjust::None {
+        return;
+    }
+
+    out.push_str(&format!(
+        "Adj p-Value: \t{}\n",
+        &science_pretty_format(adj_p, 3)
+    ));
+
+    if adj_p <= clevel {
+        out.push_str("Adj Sig:   \tSignificant\n");
+    } else {
+        out.push_str("Adj Sig:   \tNot Significant\n");
+    }
+}
+
+// Lay out one value per pair as an upper triangular matrix
+fn pair_matrix<F: Fn(&Pairresults) -> String>(m: &Multisample, cell: F) -> String {
+    let mut out: String = String::from("");
+
+    for name in &m.names {
+        out.push('\t');
+        out.push_str(name);
+    }
+    out.push('\n');
+
+    for (i, name) in m.names.iter().enumerate() {
+        out.push_str(name);
+        for j in 0..m.names.len() {
+            out.push('\t');
+            if let Some(p) = m.pairs.iter().find(|p| p.i == i && p.j == j) {
+                out.push_str(&cell(p));
+            } else if i == j {
+                out.push('-');
+            }
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+// Count missing values and the paired rows they cost, only when there are any
+fn missing_section(out: &mut String, s: &Settings, m: &Missingresults, names: &[&str]) {
+    if m.counts.iter().all(|c| *c == 0) {
+        return;
+    }
+
+    out.push('\n');
+    for (name, count) in names.iter().zip(&m.counts) {
+        out.push_str(&format!("Missing {}: \t{}\n", name, count));
+    }
+    if s.paired {
+        out.push_str(&format!(
+            "Dropped Rows: \t{} ({})\n",
+            m.dropped,
+            s.missing.name()
+        ));
+    }
+}
+
+// Text report of a batch, one block per metric with the p value adjusted across the metrics
+pub fn batch_report(b: &Batch) -> String {
+    let mut out = String::new();
+    let clevel = b.settings.clevel();
+
+    out.push_str(&format!("Metrics:   \t{}\n", b.rows.len()));
+    out.push_str(&format!("Adjust:    \t{}\n", b.settings.adjust.name()));
+    if let Some(seed) = b.settings.seed {
+        out.push_str(&format!("Seed:      \t{}\n", seed));
+    }
+    for r in &b.rows {
+        out.push_str("\n************************************\n");
+        out.push_str(&format!("Metric:    \t{}\n", r.metric));
+        out.push_str(&format!("Groups:    \t{}\n", r.names.join(", ")));
+
+        let c = match &r.result {
+            Ok(c) => c,
+            Err(e) => {
+                out.push_str(&format!("Error:     \t{}\n", e));
+                continue;
+            }
+        };
+        let s = &c.sdmean;
+
+        out.push_str(&format!("Count A:   \t{}\n", c.a.count));
+        out.push_str(&format!("Count B:   \t{}\n", c.b.count));
+        out.push_str(&format!(
+            "Mean A:    \t{}\n",
+            &science_pretty_format(s.amm, 6)
+        ));
+        out.push_str(&format!(
+            "Mean B:    \t{}\n",
+            &science_pretty_format(s.bmm, 6)
+        ));
+        out.push_str(&format!(
+            "Mean Diff: \t{}\n",
+            &science_pretty_format(s.dmm, 6)
+        ));
+        // One tailed tests only have the bound on the tested side, as in the summary table
+        match bounds(&b.settings, s.amm > s.bmm) {
+            (true, true) => out.push_str(&format!(
+                "CI:        \t{} to {}\n",
+                &science_pretty_format(s.dml, 6),
+                &science_pretty_format(s.dmu, 6)
+            )),
+            (true, false) => out.push_str(&format!(
+                "CI Low:    \t{}\n",
+                &science_pretty_format(s.dml, 6)
+            )),
+            _ => out.push_str(&format!(
+                "CI High:   \t{}\n",
+                &science_pretty_format(s.dmu, 6)
+            )),
+        }
+        out.push_str(&format!(
+            "p-Value:   \t{}\n",
+            &science_pretty_format(c.mean_p, 3)
+        ));
+        out.push_str(&format!(
+            "Adj p-Value: \t{}\n",
+            &science_pretty_format(r.adj_p, 3)
+        ));
+        if r.adj_p <= clevel {
+            out.push_str("Adj Sig:   \tSignificant\n");
+        } else {
+            out.push_str("Adj Sig:   \tNot Significant\n");
+        }
+    }
+
+    out
+}
+
+// The block of the text report around a position, between the star lines, as clean tab separated lines
+pub fn section_tsv(text: &str, pos: usize) -> String {
+    let pos = pos.min(text.len());
+    let sep = "************************************";
+
+    let start = text[..pos].rfind(sep).map_or(0, |i| i + sep.len());
+    let end = text[pos..].find(sep).map_or(text.len(), |i| pos + i);
+
+    let mut out = String::new();
+    for line in text[start..end].lines() {
+        let cells: Vec<&str> = line
+            .split('\t')
+            .map(|c| c.trim().trim_end_matches(':').trim_end())
+            .collect();
+        if cells.iter().all(|c| c.is_empty()) {
+            continue;
+        }
+        out.push_str(&cells.join("\t"));
+        out.push('\n');
+    }
+    out
+}
+
+// List the tokens of one data column that were not numbers
+pub fn rejected_tokens(name: &str, rejected: &[Rejected]) -> String {
+    let mut out = format!("Rejected {}: \t{}\n", name, rejected.len());
+
+    for r in rejected {
+        out.push_str(&format!(
+            "  Line {}, Col {}: \t{}\n",
+            r.line, r.column, r.token
+        ));
+    }
+    out
+}
+
+// List the lines of one data column that look like a decimal comma but were read as two values
+pub fn suspicious_tokens(name: &str, suspicious: &[Rejected]) -> String {
+    let mut out = format!(
+        "Check {}: \t{} read as two values\n",
+        name,
+        suspicious.len()
+    );
+
+    for r in suspicious {
+        out.push_str(&format!(
+            "  Line {}, Col {}: \t{}\n",
+            r.line, r.column, r.token
+        ));
+    }
+    out
+}
+
+// Pretty Format Scientific Numbers
+pub fn science_pretty_format(value: f64, digits: usize) -> String {
+    if value.abs() == 0.0 {
+        return "0".to_string();
+    }
+    if value.abs() >= 10000.0 || value.abs() < 0.001 {
+        return format!("{:.*e}", digits, value);
+    }
+    format!("{:.*}", digits, value)
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn section_tsv_takes_the_section_at_the_cursor() {
+        let text = "Count A: \t3\n************************************\n\nMean A:    \t1.5\t[1, 2]\n\t\n************************************\nEnd";
+        let pos = text.find("1.5").unwrap();
+        assert_eq!(section_tsv(text, pos), "Mean A\t1.5\t[1, 2]\n");
+
+        assert_eq!(section_tsv(text, 0), "Count A\t3\n");
+        assert_eq!(section_tsv(text, text.len() + 10), "End\n");
+    }
+
+    #[test]
+    fn kendall_keeps_its_strength_label() {
+        let a = Sample {
+            name: "A".to_string(),
+            values: vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0],
+        };
+        let b = Sample {
+            name: "B".to_string(),
+            values: vec![2.0, 1.0, 4.0, 3.0, 6.0, 5.0, 8.0, 9.0],
+        };
+        let s = Settings {
+            paired: true,
+            iterations: 1000,
+            seed: Some(1),
+            ..Settings::default()
+        };
+        let c = compare(&a, &b, &s).unwrap();
+        let strength = corr_strength(c.correlation.as_ref().unwrap().kendall.value);
+
+        let text = two_sample(&c);
+        let kendall = &text[text.find("Kendall's τb").unwrap()..];
+        let section = &kendall[..kendall.find("p-Value").unwrap()];
+        assert!(section.contains(&format!("Corr:      \t{}\n", strength)));
+
+        let doc = crate::document::comparison_document(&c);
+        let row = doc
+            .sections
+            .iter()
+            .flat_map(|s| &s.grid.rows)
+            .find(|r| r[0] == "Kendall's τb")
+            .unwrap();
+        assert!(row.last().unwrap().starts_with(strength));
+    }
+}
@@ -0,0 +1,124 @@
+use crate::analysis::Settings;
+use crate::document::Document;
+use crate::input::Numberformat;
+use crate::prefs::config_dir;
+use serde::{Deserialize, Serialize};
+
+use std::path::{Path, PathBuf};
+
+// Sessions kept in the recent list
+const RECENT: usize = 8;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+// Define a struct for one data column, the text as typed so comments and rejected tokens survive
+pub struct Sessiongroup {
+    pub name: String,
+    pub data: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+// Define a struct for everything needed to pick up where a session was left, the seed repeats the results
+pub struct Session {
+    pub version: u32,
+    pub settings: Settings,
+    pub strict: bool,
+    pub numbers: Numberformat,
+    pub groups: Vec<Sessiongroup>,
+    pub results: String,
+    pub document: Document,
+}
+
+// Write a session as JSON for a .json file name, TOML otherwise
+pub fn save_session(s: &Session, path: &Path) -> Result<(), String> {
+    let s = Session {
+        version: 1,
+        ..s.clone()
+    };
+    let text = if is_json(path) {
+        serde_json::to_string_pretty(&s).map_err(|e| e.to_string())?
+    } else {
+        toml::to_string(&s).map_err(|e| e.to_string())?
+    };
+
+    std::fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// Read a session written by save_session
+pub fn load_session(path: &Path) -> Result<Session, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    if is_json(path) {
+        serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    } else {
+        toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+}
+
+// The recent sessions, newest first, leaving out files that are gone
+pub fn recent_sessions() -> Vec<PathBuf> {
+    let text = recent_file()
+        .and_then(|f| std::fs::read_to_string(f).ok())
+        .unwrap_or_default();
+
+    text.lines()
+        .map(PathBuf::from)
+        .filter(|p| p.is_file())
+        .take(RECENT)
+        .collect()
+}
+
+// Move a session to the top of the recent list, the list is a convenience so failures are ignored
+pub fn remember_session(path: &Path) {
+    let Some(file) = recent_file() else {
+        return;
+    };
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+    let mut recent = recent_sessions();
+    recent.retain(|p| *p != path);
+    recent.insert(0, path);
+    recent.truncate(RECENT);
+
+    let lines: Vec<String> = recent.iter().map(|p| p.display().to_string()).collect();
+    if let Some(dir) = file.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let _ = std::fs::write(file, lines.join("\n") + "\n");
+}
+
+fn recent_file() -> Option<PathBuf> {
+    config_dir().map(|d| d.join("recent.txt"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::MAX_SEED;
+
+    #[test]
+    fn large_seed_round_trips() {
+        let s = Session {
+            settings: Settings {
+                seed: Some(MAX_SEED),
+                ..Settings::default()
+            },
+            ..Session::default()
+        };
+        let dir = std::env::temp_dir();
+
+        for name in ["large_seed.toml", "large_seed.json"] {
+            let path = dir.join(name);
+            save_session(&s, &path).unwrap();
+            let back = load_session(&path).unwrap();
+            let _ = std::fs::remove_file(&path);
+            assert_eq!(back.settings.seed, Some(MAX_SEED));
+        }
+    }
+}
@@ -0,0 +1,1275 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::f64;
+
+// The seed to resample with, a fresh one when none is given so a run can still be repeated
+// Drawn seeds stay below 2^53 so they survive JSON and TOML files
+pub fn draw_seed(seed: Option<u64>) -> u64 {
+    seed.unwrap_or_else(|| rand::thread_rng().gen_range(0..1 << 53))
+}
+
+#[derive(Clone, Debug, Serialize)]
+// Define a struct for our Z Score Counts
+pub struct Zscoreresults {
+    pub pluscount: usize,
+    pub minuscount: usize,
+    pub pluspercent: f64,
+    pub minuspercent: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+// Define the multiple comparison corrections
+pub enum Adjust {
+    None,
+    Bonferroni,
+    Holm,
+    BenjaminiHochberg,
+}
+
+impl Adjust {
+    // Name the correction for display
+    pub fn name(&self) -> &'static str {
+        match self {
+            Adjust::None => "None",
+            Adjust::Bonferroni => "Bonferroni",
+            Adjust::Holm => "Holm",
+            Adjust::BenjaminiHochberg => "Benjamini-Hochberg",
+        }
+    }
+
+    // Find a correction by its name or short name, ignoring case
+    pub fn from_name(name: &str) -> Result<Adjust, String> {
+        match name.trim().to_lowercase().as_str() {
+            "none" => Ok(Adjust::None),
+            "bonferroni" => Ok(Adjust::Bonferroni),
+            "holm" => Ok(Adjust::Holm),
+            "bh" | "benjamini-hochberg" => Ok(Adjust::BenjaminiHochberg),
+            m => Err(format!("Unknown adjustment {}", m)),
+        }
+    }
+}
+
+// A statistic calculated from paired data
+pub type Pairstat = fn(&[f64], &[f64]) -> f64;
+
+#[derive(Clone, Debug, Serialize)]
+// Define a struct for a bootstrapped statistic with its CI and p value
+pub struct Statresults {
+    pub value: f64,
+    pub l: f64,
+    pub u: f64,
+    pub p: f64,
+    pub adj_p: f64,
+}
+
+#[derive(Clone, Debug)]
+// Define a struct for the pair counts behind Kendall's Tau-b
+struct Kendallcounts {
+    s: f64,
+    n0: f64,
+    n1: f64,
+    n2: f64,
+    ties_x: Vec<f64>,
+    ties_y: Vec<f64>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+// Define a struct for CI results
+pub struct CIresults {
+    pub mu: f64,
+    pub ml: f64,
+    pub mm: f64,
+    pub su: f64,
+    pub sl: f64,
+    pub sm: f64,
+}
+
+// Calculate a bootstrapped mean and confidence interval for an array of data
+pub fn ci(v: &[f64], iterations: i32, clevel: f64, rng: &mut StdRng) -> CIresults {
+    ci_dist(v, iterations, clevel, rng).0
+}
+
+// Calculate a bootstrapped CI, also keeping the sorted bootstrap means
+pub fn ci_dist(v: &[f64], iterations: i32, clevel: f64, rng: &mut StdRng) -> (CIresults, Vec<f64>) {
+    let mut tmp: Vec<f64> = Vec::new();
+    let mut means: Vec<f64> = Vec::new();
+    let mut sds: Vec<f64> = Vec::new();
+
+    let len = v.len();
+
+    for _i in 0..iterations {
+        tmp.clear();
+        for _j in 0..len {
+            tmp.push(v[rng.gen_range(0..len)]);
+        }
+        let m: f64 = mean(&tmp);
+        means.push(m);
+        sds.push(sd_sample(&tmp, &m));
+    }
+
+    means.sort_by(cmp_f64);
+    sds.sort_by(cmp_f64);
+
+    let c = CIresults {
+        mm: (means[(iterations / 2) as usize]),
+        ml: (means[(iterations as f64 * clevel) as usize]),
+        mu: (means[(iterations as f64 * (1.0 - clevel)) as usize]),
+        sm: (sds[(iterations / 2) as usize]),
+        sl: (sds[(iterations as f64 * clevel) as usize]),
+        su: (sds[(iterations as f64 * (1.0 - clevel)) as usize]),
+    };
+
+    (c, means)
+}
+
+// Thin a sorted distribution down to at most n evenly spaced quantiles
+pub fn thin(sorted: &[f64], n: usize) -> Vec<f64> {
+    if sorted.len() <= n {
+        return sorted.to_vec();
+    }
+
+    (0..n).map(|i| sorted[i * sorted.len() / n]).collect()
+}
+
+// Count values into equal width bins between lo and hi
+pub fn histogram(v: &[f64], lo: f64, hi: f64, bins: usize) -> Vec<f64> {
+    let mut counts: Vec<f64> = vec![0.0; bins];
+    let width = (hi - lo) / bins as f64;
+
+    for x in v {
+        if *x < lo || *x > hi {
+            continue;
+        }
+        let i = (((x - lo) / width) as usize).min(bins - 1);
+        counts[i] += 1.0;
+    }
+    counts
+}
+
+// Pick a bin count with the Freedman-Diaconis rule, falling back to Sturges
+pub fn bin_count(v: &[f64]) -> usize {
+    let mut s = v.to_vec();
+    s.sort_by(cmp_f64);
+
+    let n = s.len() as f64;
+    let sturges = (n.log2() + 1.0).ceil();
+    let iqr = s[(s.len() * 3) / 4] - s[s.len() / 4];
+    let span = s[s.len() - 1] - s[0];
+
+    let bins = if iqr > 0.0 {
+        span / (2.0 * iqr / n.cbrt())
+    } else {
+        sturges
+    };
+
+    (bins.ceil() as usize).clamp(1, 60)
+}
+
+// Quantile of sorted values, interpolating between neighbours
+pub fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let h = (sorted.len() - 1) as f64 * q;
+    let i = h.floor() as usize;
+
+    if i + 1 >= sorted.len() {
+        return sorted[sorted.len() - 1];
+    }
+    sorted[i] + (h - i as f64) * (sorted[i + 1] - sorted[i])
+}
+
+// Gaussian kernel density estimate at some points, Silverman's bandwidth
+pub fn kde(v: &[f64], at: &[f64]) -> Vec<f64> {
+    let mut s = v.to_vec();
+    s.sort_by(cmp_f64);
+
+    let n = s.len() as f64;
+    let sd = sd_sample(&s, &mean(&s));
+    let iqr = quantile(&s, 0.75) - quantile(&s, 0.25);
+    let spread = if iqr > 0.0 { sd.min(iqr / 1.34) } else { sd };
+    let bw = if spread > 0.0 {
+        0.9 * spread * n.powf(-0.2)
+    } else {
+        1.0
+    };
+
+    at.iter()
+        .map(|x| {
+            s.iter()
+                .map(|xi| (-0.5 * ((x - xi) / bw).powi(2)).exp())
+                .sum::<f64>()
+                / (n * bw * (2.0 * std::f64::consts::PI).sqrt())
+        })
+        .collect()
+}
+
+// Bootstrap several statistics of paired data by resampling the pairs
+pub fn ci_pairs(
+    x: &[f64],
+    y: &[f64],
+    iterations: i32,
+    clevel: f64,
+    stats: &[Pairstat],
+    rng: &mut StdRng,
+) -> Vec<Statresults> {
+    let mut tmp_x: Vec<f64> = Vec::new();
+    let mut tmp_y: Vec<f64> = Vec::new();
+    let mut values: Vec<Vec<f64>> = vec![Vec::new(); stats.len()];
+
+    let len = x.len();
+
+    for _i in 0..iterations {
+        tmp_x.clear();
+        tmp_y.clear();
+        for _j in 0..len {
+            let k = rng.gen_range(0..len);
+            tmp_x.push(x[k]);
+            tmp_y.push(y[k]);
+        }
+        for (s, v) in stats.iter().zip(values.iter_mut()) {
+            v.push(s(&tmp_x, &tmp_y));
+        }
+    }
+
+    stats
+        .iter()
+        .zip(values.iter_mut())
+        .map(|(s, v)| {
+            v.sort_by(cmp_f64);
+
+            let value = s(x, y);
+            let l = v[(iterations as f64 * clevel) as usize];
+            let u = v[(iterations as f64 * (1.0 - clevel)) as usize];
+            let p = p_from_ci(l, u, value, 1.0 - clevel);
+
+            Statresults {
+                value,
+                l,
+                u,
+                p,
+                adj_p: p,
+            }
+        })
+        .collect()
+}
+
+// Calculate mean
+pub fn mean(vec: &[f64]) -> f64 {
+    let sum: f64 = Iterator::sum(vec.iter());
+    sum / vec.len() as f64
+}
+
+// Calculate median
+pub fn median(vec: &[f64]) -> f64 {
+    let mut v = vec.to_owned();
+
+    v.sort_by(cmp_f64);
+    v[vec.len() / 2]
+}
+
+// Find uniques and count them
+pub fn count_unique_values(vec: &[f64]) -> (Vec<f64>, Vec<i32>) {
+    let mut v = vec.to_owned();
+    let mut pv: f64;
+    let mut count: i32;
+    let mut values_out: Vec<f64> = Vec::new();
+    let mut counts_out: Vec<i32> = Vec::new();
+
+    v.sort_by(cmp_f64);
+
+    count = 0;
+    pv = v[0];
+
+    for cv in v {
+        if cv == pv {
+            count += 1;
+        } else {
+            values_out.push(pv);
+            counts_out.push(count);
+            pv = cv;
+            count = 1;
+        }
+    }
+    values_out.push(pv);
+    counts_out.push(count);
+
+    (values_out, counts_out)
+}
+
+// Calculate Percent difference
+pub fn per_change(f: &f64, s: &f64) -> f64 {
+    (s - f) / f.abs() * 100.0
+}
+
+// Comparison function for vec<64> sorting
+pub fn cmp_f64(a: &f64, b: &f64) -> Ordering {
+    if a.is_nan() {
+        return Ordering::Greater;
+    }
+    if b.is_nan() {
+        return Ordering::Less;
+    }
+    if a < b {
+        return Ordering::Less;
+    } else if a > b {
+        return Ordering::Greater;
+    }
+    Ordering::Equal
+}
+
+// Calculate SD of a sample
+pub fn sd_sample(x: &[f64], mean: &f64) -> f64 {
+    let mut sd: f64 = 0.0;
+
+    for v in x.iter() {
+        sd += (v - mean).powf(2.0);
+    }
+    (sd / (x.len() - 1) as f64).sqrt()
+}
+
+// Calculate SD of a sample
+pub fn sd_pop(x: &[f64], mean: &f64) -> f64 {
+    let mut sd: f64 = 0.0;
+
+    for v in x.iter() {
+        sd += (v - mean).powf(2.0);
+    }
+    (sd / x.len() as f64).sqrt()
+}
+
+// Calculate Skewness
+pub fn skewness(vec: &[f64], mean: &f64, sd: &f64) -> f64 {
+    let sz: f64 = vec.len() as f64;
+    let mut tmpsum: f64 = 0.0;
+    let sdp = sd.powf(3.0);
+
+    for v in &mut vec.iter() {
+        tmpsum += (v - mean).powf(3.0) / sdp;
+    }
+
+    (sz / ((sz - 1.0) * (sz - 2.0))) * tmpsum
+}
+
+// Calculate Kurtosis
+pub fn kurtosis(vec: &[f64], mean: &f64, sd: &f64) -> f64 {
+    let sz: f64 = vec.len() as f64;
+    let mut tmpsum: f64 = 0.0;
+    let sdp = sd.powf(4.0);
+
+    for v in &mut vec.iter() {
+        tmpsum += (v - mean).powf(4.0) / sdp;
+    }
+
+    (((sz * (sz + 1.0)) / ((sz - 1.0) * (sz - 2.0) * (sz - 3.0))) * tmpsum)
+        - ((3.0 * (sz - 1.0) * (sz - 1.0)) / ((sz - 2.0) * (sz - 3.0)))
+}
+
+// Rankify
+pub fn rankify(x: &[f64]) -> Vec<f64> {
+    let n = x.len();
+    let mut rank: Vec<f64> = Vec::new();
+
+    for i in 0..n {
+        let mut r = 1;
+        let mut s = 1;
+
+        for j in 0..i {
+            if x[j] < x[i] {
+                r += 1;
+            }
+            if (x[j] - x[i]).abs() < f64::EPSILON {
+                s += 1;
+            }
+        }
+
+        for j in (i + 1)..n {
+            if x[j] < x[i] {
+                r += 1;
+            }
+            if (x[j] - x[i]).abs() < f64::EPSILON {
+                s += 1;
+            }
+        }
+
+        rank.push(r as f64 + (s as f64 - 1.0) * 0.5);
+    }
+    rank
+}
+
+// Calculate R Correlation
+pub fn r_value(x: Vec<f64>, y: Vec<f64>) -> f64 {
+    let mut xmx_sum: f64 = 0.0;
+    let mut ymy_sum: f64 = 0.0;
+    let mut xmx_ymy_sum: f64 = 0.0;
+
+    let mx = mean(&x);
+    let my = mean(&y);
+
+    for i in 0..x.len() {
+        xmx_sum += (x[i] - mx) * (x[i] - mx);
+        ymy_sum += (y[i] - my) * (y[i] - my);
+        xmx_ymy_sum += (x[i] - mx) * (y[i] - my);
+    }
+
+    xmx_ymy_sum / (xmx_sum * ymy_sum).sqrt()
+}
+
+// Calculate Pearson's r of paired data
+pub fn pearson(x: &[f64], y: &[f64]) -> f64 {
+    r_value(x.to_vec(), y.to_vec())
+}
+
+// Calculate Kendall's Tau-b of paired data
+pub fn kendall(x: &[f64], y: &[f64]) -> f64 {
+    let k = kendall_counts(x, y);
+
+    k.s / ((k.n0 - k.n1) * (k.n0 - k.n2)).sqrt()
+}
+
+// Calculate a two tailed p value for Kendall's Tau-b, normal approximation with tie correction
+pub fn p_from_kendall(x: &[f64], y: &[f64]) -> f64 {
+    // The variance needs three or more pairs
+    if x.len() < 3 {
+        return f64::NAN;
+    }
+
+    let k = kendall_counts(x, y);
+    let n = x.len() as f64;
+    let m = n * (n - 1.0);
+
+    let mut x0: f64 = 0.0;
+    let mut x1: f64 = 0.0;
+    let mut y0: f64 = 0.0;
+    let mut y1: f64 = 0.0;
+
+    for t in &k.ties_x {
+        x0 += t * (t - 1.0) * (t - 2.0);
+        x1 += t * (t - 1.0) * (2.0 * t + 5.0);
+    }
+    for t in &k.ties_y {
+        y0 += t * (t - 1.0) * (t - 2.0);
+        y1 += t * (t - 1.0) * (2.0 * t + 5.0);
+    }
+
+    let var = (m * (2.0 * n + 5.0) - x1 - y1) / 18.0
+        + (2.0 * k.n1 * k.n2) / m
+        + x0 * y0 / (9.0 * m * (n - 2.0));
+    let z = k.s / var.sqrt();
+
+    (1.0 - p_from_z(z.abs())) * 2.0
+}
+
+// Count concordant minus discordant pairs and ties in O(n log n) using Knight's algorithm
+fn kendall_counts(x: &[f64], y: &[f64]) -> Kendallcounts {
+    let n = x.len();
+    let mut idx: Vec<usize> = (0..n).collect();
+
+    // Sort by x, breaking ties with y
+    idx.sort_by(|&i, &j| cmp_f64(&x[i], &x[j]).then(cmp_f64(&y[i], &y[j])));
+
+    let xs: Vec<f64> = idx.iter().map(|&i| x[i]).collect();
+    let mut ys: Vec<f64> = idx.iter().map(|&i| y[i]).collect();
+
+    // Ties in x and joint ties in x and y
+    let ties_x = tie_runs(&xs);
+    let mut n3: f64 = 0.0;
+    let mut i = 0;
+
+    while i < n {
+        let mut j = i + 1;
+        while j < n && xs[j] == xs[i] && ys[j] == ys[i] {
+            j += 1;
+        }
+        let t = (j - i) as f64;
+        n3 += t * (t - 1.0) / 2.0;
+        i = j;
+    }
+
+    // Every swap needed to sort y is a discordant pair
+    let mut buf: Vec<f64> = ys.clone();
+    let swaps = merge_count(&mut ys, &mut buf) as f64;
+
+    let ties_y = tie_runs(&ys);
+    let n0 = (n * n.saturating_sub(1)) as f64 / 2.0;
+    let n1: f64 = ties_x.iter().map(|t| t * (t - 1.0) / 2.0).sum();
+    let n2: f64 = ties_y.iter().map(|t| t * (t - 1.0) / 2.0).sum();
+
+    Kendallcounts {
+        s: n0 - n1 - n2 + n3 - 2.0 * swaps,
+        n0,
+        n1,
+        n2,
+        ties_x,
+        ties_y,
+    }
+}
+
+// Sizes of the runs of equal values in sorted data
+fn tie_runs(v: &[f64]) -> Vec<f64> {
+    let mut runs: Vec<f64> = Vec::new();
+    let mut i = 0;
+
+    while i < v.len() {
+        let mut j = i + 1;
+        while j < v.len() && v[j] == v[i] {
+            j += 1;
+        }
+        if j - i > 1 {
+            runs.push((j - i) as f64);
+        }
+        i = j;
+    }
+    runs
+}
+
+// Merge sort counting the swaps, equal values are not swapped
+fn merge_count(v: &mut [f64], buf: &mut [f64]) -> u64 {
+    let n = v.len();
+
+    if n < 2 {
+        return 0;
+    }
+
+    let mid = n / 2;
+    let mut swaps = merge_count(&mut v[..mid], &mut buf[..mid]);
+    swaps += merge_count(&mut v[mid..], &mut buf[mid..]);
+
+    let (mut i, mut j, mut k) = (0, mid, 0);
+
+    while i < mid && j < n {
+        if v[j] < v[i] {
+            buf[k] = v[j];
+            swaps += (mid - i) as u64;
+            j += 1;
+        } else {
+            buf[k] = v[i];
+            i += 1;
+        }
+        k += 1;
+    }
+    buf[k..k + mid - i].copy_from_slice(&v[i..mid]);
+    k += mid - i;
+    buf[k..k + n - j].copy_from_slice(&v[j..n]);
+
+    v.copy_from_slice(&buf[..n]);
+    swaps
+}
+
+// Calculate the OLS slope of y on x
+pub fn ols_slope(x: &[f64], y: &[f64]) -> f64 {
+    let mx = mean(x);
+    let my = mean(y);
+    let mut sxy: f64 = 0.0;
+    let mut sxx: f64 = 0.0;
+
+    for i in 0..x.len() {
+        sxy += (x[i] - mx) * (y[i] - my);
+        sxx += (x[i] - mx) * (x[i] - mx);
+    }
+
+    sxy / sxx
+}
+
+// Calculate the OLS intercept of y on x
+pub fn ols_intercept(x: &[f64], y: &[f64]) -> f64 {
+    mean(y) - ols_slope(x, y) * mean(x)
+}
+
+// Calculate R^2
+pub fn r2_value(x: &[f64], y: &[f64]) -> f64 {
+    let mut xy_sum: f64 = 0.0;
+    let mut x_sum: f64 = 0.0;
+    let mut y_sum: f64 = 0.0;
+    let mut x2_sum: f64 = 0.0;
+    let mut y2_sum: f64 = 0.0;
+    let n = x.len() as f64;
+
+    for i in 0..x.len() {
+        xy_sum += x[i] * y[i];
+        x_sum += x[i];
+        y_sum += y[i];
+        x2_sum += x[i] * x[i];
+        y2_sum += y[i] * y[i];
+    }
+
+    let r: f64 = (n * xy_sum - x_sum * y_sum)
+        / ((n * x2_sum - x_sum * x_sum) * (n * y2_sum - y_sum * y_sum)).sqrt();
+    r * r
+}
+
+// Calculate Log Gamma
+pub fn l_gamma(x: f64) -> f64 {
+    let coef: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941678,
+        24.01409824083091,
+        -1.231739572450155,
+        1.208650973866179E-3,
+        -0.5395239384953E-5,
+    ];
+    let logsqrttwopi: f64 = 0.9189385332046728;
+    let y: f64 = x + 5.5;
+    let mut denom: f64 = x + 1.0;
+    let mut series: f64 = 1.000000000190015;
+
+    for v in &coef {
+        series += v / denom;
+        denom += 1.0;
+    }
+
+    logsqrttwopi + (x + 0.5) * y.ln() - y + (series / x).ln()
+}
+
+// Calculate a p value from F and A count, B count
+pub fn p_from_f(f: f64, df1: usize, df2: usize) -> f64 {
+    1.0 - incomplete_beta(
+        df1 as f64 * f / (df1 as f64 * f + df2 as f64),
+        0.5 * df1 as f64,
+        0.5 * df2 as f64,
+    )
+}
+
+// Calculate incomplete Beta
+pub fn incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if (x - 0.0).abs() < f64::EPSILON {
+        return 0.0;
+    }
+
+    if (x - 1.0).abs() < f64::EPSILON {
+        return 1.0;
+    }
+
+    let l_beta: f64 = l_gamma(a + b) - l_gamma(a) - l_gamma(b) + a * x.ln() + b * (1.0 - x).ln();
+
+    if x < (a + 1.0) / (a + b + 2.0) {
+        l_beta.exp() * contfrac_beta(x, a, b) / a
+    } else {
+        1.0 - l_beta.exp() * contfrac_beta(1.0 - x, b, a) / b
+    }
+}
+
+fn contfrac_beta(x: f64, a: f64, b: f64) -> f64 {
+    let itmax: usize = 200;
+    let eps: f64 = 3.0e-7;
+
+    let mut bm = 1.0;
+    let mut az = 1.0;
+    let mut am = 1.0;
+    let qab = a + b;
+    let qap = a + 1.0;
+    let qam = a - 1.0;
+    let mut bz = 1.0 - qab * x / qap;
+    let mut em: f64;
+    let mut tem: f64;
+    let mut d: f64;
+    let mut ap: f64;
+    let mut bp: f64;
+    let mut app: f64;
+    let mut bpp: f64;
+    let mut aold: f64;
+
+    for i in 0..itmax {
+        em = i as f64 + 1.0;
+        tem = em + em;
+        d = em * (b - em) * x / ((qam + tem) * (a + tem));
+        ap = az + d * am;
+        bp = bz + d * bm;
+        d = -(a + em) * (qab + em) * x / ((qap + tem) * (a + tem));
+        app = ap + d * az;
+        bpp = bp + d * bz;
+        aold = az;
+        am = ap / bpp;
+        bm = bp / bpp;
+        az = app / bpp;
+        bz = 1.0;
+        if (az - aold).abs() < eps * az.abs() {
+            return az;
+        }
+    }
+
+    0.0
+}
+
+// Calculate P Value from T statistic
+pub fn p_from_t(ws: f64, dof: f64) -> f64 {
+    let a: f64 = dof / 2.0;
+    let mut value = dof / (ws * ws + dof);
+
+    if value.is_infinite() || value.is_nan() {
+        return 1.0;
+    }
+
+    let beta = l_gamma(a) + 0.5723649429247001 - l_gamma(a + 0.5);
+    let acu = 0.1E-14;
+    let mut ai: f64;
+    let mut cx: f64;
+    let mut ns: i32;
+    let mut psq: f64;
+    let mut rx: f64;
+    let mut temp: f64;
+    let mut term: f64;
+    let xx: f64;
+    let qq: f64;
+    let pp: f64;
+    let indx: i32;
+
+    if !(0.0..=1.0).contains(&value) {
+        return value;
+    }
+
+    if (value - 0.0).abs() < f64::EPSILON || (value - 1.0).abs() < f64::EPSILON {
+        return value;
+    }
+
+    psq = a + 0.5;
+    cx = 1.0 - value;
+
+    if a < psq * value {
+        xx = cx;
+        cx = value;
+        pp = 0.5;
+        qq = a;
+        indx = 1;
+    } else {
+        xx = value;
+        pp = a;
+        qq = 0.5;
+        indx = 0;
+    }
+
+    term = 1.0;
+    ai = 1.0;
+    value = 1.0;
+    ns = (qq + cx * psq) as i32;
+    rx = xx / cx;
+    temp = qq - ai;
+
+    if ns == 0 {
+        rx = xx;
+    }
+
+    loop {
+        term = term * temp * rx / (pp + ai);
+        value += term;
+        temp = term.abs();
+
+        if temp <= acu && temp <= acu * value {
+            value = value * (pp * xx.ln() + (qq - 1.0) * cx.ln() - beta).exp() / pp;
+
+            if indx != 0 {
+                value = 1.0 - value;
+            }
+
+            break;
+        }
+
+        ai += 1.0;
+        ns -= 1;
+
+        if 0 <= ns {
+            temp = qq - ai;
+
+            if ns == 0 {
+                rx = xx;
+            }
+        } else {
+            temp = psq;
+            psq += 1.0;
+        }
+    }
+
+    value
+}
+
+// Calculate P-Value from CI
+pub fn p_from_ci(l: f64, u: f64, m: f64, cl: f64) -> f64 {
+    let s: f64 = erf_inv(cl) * f64::consts::SQRT_2;
+    let se: f64 = (u - l) / (2.0 * s);
+    let z: f64 = m / se;
+
+    (1.0 - p_from_z(z.abs())) * 2.0
+}
+
+// Calculate inverse ERF
+pub fn erf_inv(x: f64) -> f64 {
+    let mut w: f64;
+    let mut p: f64;
+
+    w = -((1.0 - x) * (1.0 + x)).ln();
+
+    if w < 5.000000 {
+        w -= 2.500000;
+        p = 2.81022636e-08;
+        p = 3.43273939e-07 + p * w;
+        p = -3.5233877e-06 + p * w;
+        p = -4.39150654e-06 + p * w;
+        p = 0.00021858087 + p * w;
+        p = -0.00125372503 + p * w;
+        p = -0.00417768164 + p * w;
+        p = 0.246640727 + p * w;
+        p = 1.50140941 + p * w;
+    } else {
+        w = w.sqrt() - 3.000000;
+        p = -0.000200214257;
+        p = 0.000100950558 + p * w;
+        p = 0.00134934322 + p * w;
+        p = -0.00367342844 + p * w;
+        p = 0.00573950773 + p * w;
+        p = -0.0076224613 + p * w;
+        p = 0.00943887047 + p * w;
+        p = 1.00167406 + p * w;
+        p = 2.83297682 + p * w;
+    }
+
+    p * x
+}
+
+// Calculate Z from Confidence Level
+pub fn _z_from_cl(cl: f64) -> f64 {
+    erf_inv(cl) * f64::consts::SQRT_2
+}
+
+// Standard normal quantile for a cumulative probability
+pub fn z_from_p(p: f64) -> f64 {
+    erf_inv(2.0 * p - 1.0) * f64::consts::SQRT_2
+}
+
+pub fn zcount(x: &[f64], zth: f64) -> Zscoreresults {
+    let mean = mean(x);
+    let sd = sd_pop(x, &mean);
+    let mut zresults: Zscoreresults = Zscoreresults {
+        pluscount: 0,
+        minuscount: 0,
+        pluspercent: 0.0,
+        minuspercent: 0.0,
+    };
+
+    for v in x {
+        let z = (v - mean) / sd;
+
+        if z >= zth.abs() {
+            zresults.pluscount += 1;
+        }
+        if z <= -zth.abs() {
+            zresults.minuscount += 1;
+        }
+    }
+
+    zresults.pluspercent = (zresults.pluscount as f64 / x.len() as f64) * 100.0;
+    zresults.minuspercent = (zresults.minuscount as f64 / x.len() as f64) * 100.0;
+
+    zresults
+}
+
+// Calculate P from Z
+pub fn p_from_z(z: f64) -> f64 {
+    let mut y: f64;
+    let x: f64;
+    let w: f64;
+
+    if z == 0.0 {
+        x = 0.0;
+    } else {
+        y = 0.5 * z.abs();
+
+        if y >= 3.0 {
+            x = 1.0;
+        } else if y < 1.0 {
+            w = y * y;
+            x = ((((((((0.000124818987 * w - 0.001075204047) * w + 0.005198775019) * w
+                - 0.019198292004)
+                * w
+                + 0.059054035642)
+                * w
+                - 0.151968751364)
+                * w
+                + 0.319152932694)
+                * w
+                - 0.531923007300)
+                * w
+                + 0.797884560593)
+                * y
+                * 2.0;
+        } else {
+            y -= 2.0;
+            x = (((((((((((((-0.000045255659 * y + 0.000152529290) * y - 0.000019538132)
+                * y
+                - 0.000676904986)
+                * y
+                + 0.001390604284)
+                * y
+                - 0.000794620820)
+                * y
+                - 0.002034254874)
+                * y
+                + 0.006549791214)
+                * y
+                - 0.010557625006)
+                * y
+                + 0.011630447319)
+                * y
+                - 0.009279453341)
+                * y
+                + 0.005353579108)
+                * y
+                - 0.002141268741)
+                * y
+                + 0.000535310849)
+                * y
+                + 0.999936657524;
+        }
+    }
+
+    if z > 0.0 {
+        (x + 1.0) * 0.5
+    } else {
+        (1.0 - x) * 0.5
+    }
+}
+
+// Calculate the one way ANOVA F statistic for a set of groups
+pub fn anova_f(groups: &[Vec<f64>]) -> f64 {
+    let k = groups.len() as f64;
+    let n: f64 = groups.iter().map(|g| g.len() as f64).sum();
+    let grand: f64 = groups.iter().flatten().sum::<f64>() / n;
+    let mut ssb: f64 = 0.0;
+    let mut ssw: f64 = 0.0;
+
+    for g in groups {
+        let m = mean(g);
+        ssb += g.len() as f64 * (m - grand) * (m - grand);
+        for v in g {
+            ssw += (v - m) * (v - m);
+        }
+    }
+
+    (ssb / (k - 1.0)) / (ssw / (n - k))
+}
+
+// Bootstrap p value for the ANOVA F statistic, resampling each group around its own mean
+pub fn bootstrap_anova(groups: &[Vec<f64>], iterations: i32, rng: &mut StdRng) -> f64 {
+    let f = anova_f(groups);
+    let centered: Vec<Vec<f64>> = groups
+        .iter()
+        .map(|g| {
+            let m = mean(g);
+            g.iter().map(|v| v - m).collect()
+        })
+        .collect();
+    let mut tmp: Vec<Vec<f64>> = centered.clone();
+    let mut count = 0;
+
+    for _i in 0..iterations {
+        for (j, g) in centered.iter().enumerate() {
+            let len = g.len();
+            for v in tmp[j].iter_mut() {
+                *v = g[rng.gen_range(0..len)];
+            }
+        }
+        if anova_f(&tmp) >= f {
+            count += 1;
+        }
+    }
+
+    count as f64 / iterations as f64
+}
+
+// Calculate the Kruskal-Wallis H statistic, corrected for ties
+pub fn kruskal_h(groups: &[Vec<f64>]) -> f64 {
+    let pooled: Vec<f64> = groups.iter().flatten().copied().collect();
+    let ranks = rankify(&pooled);
+    let n = pooled.len() as f64;
+    let mut sum: f64 = 0.0;
+    let mut offset = 0;
+
+    for g in groups {
+        let r: f64 = ranks[offset..offset + g.len()].iter().sum();
+        sum += r * r / g.len() as f64;
+        offset += g.len();
+    }
+
+    let h = 12.0 / (n * (n + 1.0)) * sum - 3.0 * (n + 1.0);
+
+    let (_, counts) = count_unique_values(&pooled);
+    let ties: f64 = counts.iter().map(|&t| (t as f64).powi(3) - t as f64).sum();
+
+    // Every value tied leaves nothing to rank, no evidence of a difference
+    let correction = 1.0 - ties / (n * n * n - n);
+    if correction <= 0.0 {
+        return 0.0;
+    }
+    h / correction
+}
+
+// Calculate the repeated measures ANOVA F statistic, every group holds the same subjects in the same order
+pub fn rm_anova_f(groups: &[Vec<f64>]) -> f64 {
+    let k = groups.len() as f64;
+    let n = groups[0].len();
+    let grand: f64 = groups.iter().flatten().sum::<f64>() / (k * n as f64);
+    let mut ssg: f64 = 0.0;
+    let mut sss: f64 = 0.0;
+    let mut sst: f64 = 0.0;
+
+    for g in groups {
+        let m = mean(g);
+        ssg += n as f64 * (m - grand) * (m - grand);
+        for v in g {
+            sst += (v - grand) * (v - grand);
+        }
+    }
+    for r in 0..n {
+        let m = groups.iter().map(|g| g[r]).sum::<f64>() / k;
+        sss += k * (m - grand) * (m - grand);
+    }
+
+    let sse = sst - ssg - sss;
+    (ssg / (k - 1.0)) / (sse / ((k - 1.0) * (n as f64 - 1.0)))
+}
+
+// Bootstrap p value for the repeated measures F statistic, resampling whole subjects around each group mean
+pub fn bootstrap_rm_anova(groups: &[Vec<f64>], iterations: i32, rng: &mut StdRng) -> f64 {
+    let f = rm_anova_f(groups);
+    let centered: Vec<Vec<f64>> = groups
+        .iter()
+        .map(|g| {
+            let m = mean(g);
+            g.iter().map(|v| v - m).collect()
+        })
+        .collect();
+    let mut tmp: Vec<Vec<f64>> = centered.clone();
+    let n = groups[0].len();
+    let mut count = 0;
+
+    for _i in 0..iterations {
+        let picks: Vec<usize> = (0..n).map(|_| rng.gen_range(0..n)).collect();
+        for (t, g) in tmp.iter_mut().zip(&centered) {
+            for (v, &pick) in t.iter_mut().zip(&picks) {
+                *v = g[pick];
+            }
+        }
+        if rm_anova_f(&tmp) >= f {
+            count += 1;
+        }
+    }
+
+    count as f64 / iterations as f64
+}
+
+// Calculate the Friedman statistic for subjects measured in every group, corrected for ties within a subject
+pub fn friedman_q(groups: &[Vec<f64>]) -> f64 {
+    let k = groups.len() as f64;
+    let n = groups[0].len();
+    let mut sums: Vec<f64> = vec![0.0; groups.len()];
+    let mut ties: f64 = 0.0;
+
+    for r in 0..n {
+        let row: Vec<f64> = groups.iter().map(|g| g[r]).collect();
+        for (s, rank) in sums.iter_mut().zip(rankify(&row)) {
+            *s += rank;
+        }
+        let (_, counts) = count_unique_values(&row);
+        ties += counts
+            .iter()
+            .map(|&t| (t as f64).powi(3) - t as f64)
+            .sum::<f64>();
+    }
+
+    let n = n as f64;
+    let q =
+        12.0 / (n * k * (k + 1.0)) * sums.iter().map(|s| s * s).sum::<f64>() - 3.0 * n * (k + 1.0);
+
+    // Subjects tied across every group carry no ranking, no evidence of a difference
+    let correction = 1.0 - ties / (n * (k * k * k - k));
+    if correction <= 0.0 {
+        return 0.0;
+    }
+    q / correction
+}
+
+// Calculate a p value from a Chi Squared statistic
+pub fn p_from_chi2(x: f64, dof: f64) -> f64 {
+    1.0 - incomplete_gamma(x / 2.0, dof / 2.0)
+}
+
+// Calculate regularized incomplete Gamma
+fn incomplete_gamma(x: f64, a: f64) -> f64 {
+    let itmax: usize = 200;
+    let eps: f64 = 3.0e-7;
+    let fpmin: f64 = 1.0e-30;
+
+    if x <= 0.0 {
+        return 0.0;
+    }
+
+    let l_front: f64 = -x + a * x.ln() - l_gamma(a);
+
+    if x < a + 1.0 {
+        // Series representation
+        let mut ap = a;
+        let mut del = 1.0 / a;
+        let mut sum = del;
+
+        for _i in 0..itmax {
+            ap += 1.0;
+            del *= x / ap;
+            sum += del;
+            if del.abs() < sum.abs() * eps {
+                break;
+            }
+        }
+
+        sum * l_front.exp()
+    } else {
+        // Continued fraction representation
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / fpmin;
+        let mut d = 1.0 / b;
+        let mut h = d;
+
+        for i in 1..itmax {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < fpmin {
+                d = fpmin;
+            }
+            c = b + an / c;
+            if c.abs() < fpmin {
+                c = fpmin;
+            }
+            d = 1.0 / d;
+            let del = d * c;
+            h *= del;
+            if (del - 1.0).abs() < eps {
+                break;
+            }
+        }
+
+        1.0 - l_front.exp() * h
+    }
+}
+
+// Adjust a family of p values for multiple comparisons, NaN values are left out of the family
+pub fn p_adjust(p: &[f64], method: Adjust) -> Vec<f64> {
+    let mut adj: Vec<f64> = p.to_vec();
+    let mut order: Vec<usize> = (0..p.len()).filter(|&i| !p[i].is_nan()).collect();
+    let m = order.len() as f64;
+
+    order.sort_by(|&i, &j| cmp_f64(&p[i], &p[j]));
+
+    match method {
+        Adjust::None => {}
+        Adjust::Bonferroni => {
+            for &i in &order {
+                adj[i] = (p[i] * m).min(1.0);
+            }
+        }
+        Adjust::Holm => {
+            // Step down from the smallest p value, never letting the adjusted values decrease
+            let mut running: f64 = 0.0;
+
+            for (rank, &i) in order.iter().enumerate() {
+                running = running.max(((m - rank as f64) * p[i]).min(1.0));
+                adj[i] = running;
+            }
+        }
+        Adjust::BenjaminiHochberg => {
+            // Step up from the largest p value, never letting the adjusted values increase
+            let mut running: f64 = 1.0;
+
+            for (rank, &i) in order.iter().enumerate().rev() {
+                running = running.min((m / (rank as f64 + 1.0) * p[i]).min(1.0));
+                adj[i] = running;
+            }
+        }
+    }
+
+    adj
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    // Checked against R's p.adjust, the NaN is left out of the family
+    #[test]
+    fn p_adjust_methods() {
+        let p = [0.01, 0.04, f64::NAN, 0.03, 0.005];
+        let check = |method: Adjust, want: [f64; 4]| {
+            let adj = p_adjust(&p, method);
+            assert!(adj[2].is_nan());
+            let got = [adj[0], adj[1], adj[3], adj[4]];
+            for (g, w) in got.iter().zip(want) {
+                assert!(close(*g, w), "{:?} {:?}", method, got);
+            }
+        };
+
+        check(Adjust::None, [0.01, 0.04, 0.03, 0.005]);
+        check(Adjust::Bonferroni, [0.04, 0.16, 0.12, 0.02]);
+        check(Adjust::Holm, [0.03, 0.06, 0.06, 0.02]);
+        check(Adjust::BenjaminiHochberg, [0.02, 0.04, 0.04, 0.02]);
+    }
+
+    // Tau-b = 15 / 19 by counting pairs, the p value from the tie corrected variance
+    #[test]
+    fn kendall_with_ties() {
+        let x = [1.0, 1.0, 2.0, 3.0, 4.0, 4.0, 5.0];
+        let y = [2.0, 1.0, 3.0, 3.0, 5.0, 4.0, 4.0];
+
+        assert!(close(kendall(&x, &y), 15.0 / 19.0));
+        assert!((p_from_kendall(&x, &y) - 0.018456509657).abs() < 1e-4);
+        assert!(p_from_kendall(&x[..2], &y[..2]).is_nan());
+    }
+
+    #[test]
+    fn kruskal_without_ties() {
+        let h = kruskal_h(&[
+            vec![1.0, 2.0, 3.0],
+            vec![4.0, 5.0, 6.0],
+            vec![7.0, 8.0, 9.0],
+        ]);
+        assert!(close(h, 7.2));
+        assert!(close(p_from_chi2(h, 2.0), (-3.6f64).exp()));
+    }
+
+    #[test]
+    fn kruskal_all_tied() {
+        let h = kruskal_h(&[vec![4.0, 4.0], vec![4.0, 4.0], vec![4.0, 4.0]]);
+        assert_eq!(h, 0.0);
+        assert!(close(p_from_chi2(h, 2.0), 1.0));
+    }
+
+    #[test]
+    fn friedman_and_rm_anova() {
+        let rows = [
+            vec![1.0, 2.0, 3.0],
+            vec![4.0, 5.0, 6.0],
+            vec![7.0, 8.0, 9.0],
+        ];
+        assert!(close(friedman_q(&rows), 6.0));
+        assert_eq!(friedman_q(&[vec![1.0, 2.0], vec![1.0, 2.0]]), 0.0);
+
+        let groups = [
+            vec![1.0, 2.0, 3.0, 4.0],
+            vec![2.0, 3.0, 5.0, 4.0],
+            vec![3.0, 4.0, 4.0, 7.0],
+        ];
+        assert!(close(rm_anova_f(&groups), 6.0));
+    }
+
+    #[test]
+    fn same_seed_same_resamples() {
+        use rand::SeedableRng;
+        let v = [1.0, 2.0, 3.0, 4.0, 10.0];
+        let a = ci(&v, 1000, 0.025, &mut StdRng::seed_from_u64(7));
+        let b = ci(&v, 1000, 0.025, &mut StdRng::seed_from_u64(7));
+        assert_eq!((a.ml, a.mu, a.sl, a.su), (b.ml, b.mu, b.sl, b.su));
+        assert!(a.ml <= a.mm && a.mm <= a.mu);
+        assert_eq!(draw_seed(Some(42)), 42);
+        assert!(draw_seed(None) < 1 << 53);
+    }
+}